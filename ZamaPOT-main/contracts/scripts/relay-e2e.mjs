@@ -0,0 +1,115 @@
+// scripts/relay-e2e.mjs
+// End-to-end check of the backend mint relayer against a local node:
+//
+//   npx hardhat node            # or: anvil
+//   npx hardhat run --network localhost scripts/relay-e2e.mjs
+//
+// Deploys EnhancedTalentPassport, starts the backend with the relayer pointed
+// at it, passes a quiz with a wallet address and checks the badge on chain.
+// Set BACKEND_CMD to run a prebuilt binary instead of `cargo run`.
+import hardhat from "hardhat";
+import { spawn } from "child_process";
+
+const { ethers } = hardhat;
+const BACKEND_URL = "http://127.0.0.1:8080";
+
+const sleep = (ms) => new Promise((resolve) => setTimeout(resolve, ms));
+
+async function api(path, body) {
+  const response = await fetch(BACKEND_URL + path, body === undefined ? {} : {
+    method: "POST",
+    headers: { "content-type": "application/json" },
+    body: JSON.stringify(body),
+  });
+  if (!response.ok) {
+    throw new Error(`${path}: ${response.status} ${await response.text()}`);
+  }
+  return response.json();
+}
+
+function check(condition, message) {
+  if (!condition) throw new Error("Check failed: " + message);
+  console.log("  ✅", message);
+}
+
+async function main() {
+  const [relayer, candidate] = await ethers.getSigners();
+  console.log(" 🚀 Deploying EnhancedTalentPassport...");
+  const TalentPassport = await ethers.getContractFactory("EnhancedTalentPassport");
+  const passport = await TalentPassport.deploy();
+  await passport.deployed();
+  console.log(" ✅ Deployed to:", passport.address);
+
+  const [command, ...args] = (process.env.BACKEND_CMD || "cargo run --quiet").split(" ");
+  const backend = spawn(command, args, {
+    cwd: new URL("../../fhe-backend/", import.meta.url).pathname,
+    stdio: "inherit",
+    env: {
+      ...process.env,
+      RELAYER_RPC_URL: hardhat.network.config.url,
+      RELAYER_FROM: relayer.address, // unlocked dev account
+      PASSPORT_CONTRACT_ADDRESS: passport.address,
+      ACCEPT_LEGACY_CIPHERTEXTS: "1", // lets the script answer without a client key
+    },
+  });
+
+  try {
+    for (let attempt = 0; ; attempt++) {
+      try {
+        await api("/health");
+        break;
+      } catch (error) {
+        if (attempt > 600) throw error;
+        await sleep(500);
+      }
+    }
+
+    const session = await api("/create-session", { user_id: "relay-e2e", quiz_type: "math" });
+    const count = session.questions.length;
+    const times = [31, 47, 38, 52, 29, 44, 36, 41, 33, 49];
+    const result = await api("/evaluate-quiz", {
+      user_id: session.session_id,
+      quiz_type: "math",
+      encrypted_answers: Array.from({ length: count }, (_, i) => `enc_answer_${i}`),
+      behavior_data: {
+        answer_times: Array.from({ length: count }, (_, i) => times[i % times.length]),
+        switch_counts: Array(count).fill(0),
+        start_time: 1000,
+        end_time: 1400,
+      },
+      wallet_address: candidate.address,
+    });
+    check(result.passed && result.relay, "quiz passed and the mint was relayed");
+
+    let job;
+    for (let attempt = 0; attempt < 120; attempt++) {
+      job = await api(`/relayer/${result.certificate_id}`);
+      if (["completed", "transfer_failed", "failed"].includes(job.status.state)) break;
+      await sleep(1000);
+    }
+    console.log(" ⛓️  Relay job:", JSON.stringify(job.status));
+    check(job.status.state === "completed", "relay job completed");
+
+    const tokenId = job.status.token_id;
+    const record = await passport.talentRecords(tokenId);
+    check(await passport.ownerOf(tokenId) === candidate.address, "badge is owned by the candidate wallet");
+    check(record.certificateId === result.certificate_id, "on-chain record carries the certificate id");
+    check(await passport.usedScores(result.mint.args.encrypted_score), "score commitment is marked used");
+    // The contract mints to msg.sender, so only ERC-721 ownership moves
+    check(
+      (await passport.userBadges(relayer.address, 0)).toNumber() === tokenId,
+      "userBadges records the relayer, not the candidate (known limitation)",
+    );
+
+    const status = await api(`/certificates/${result.certificate_id}`);
+    check(status.token_id === tokenId, "registry recorded the minted token");
+    console.log(" 🎉 Relayer flow verified");
+  } finally {
+    backend.kill();
+  }
+}
+
+main().catch((error) => {
+  console.error(error);
+  process.exit(1);
+});
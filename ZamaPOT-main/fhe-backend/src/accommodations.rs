@@ -0,0 +1,89 @@
+use serde::{Deserialize, Serialize};
+
+pub const MAX_TIME_MULTIPLIER: f32 = 4.0;
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct Accommodation {
+    #[serde(default = "default_time_multiplier")]
+    pub time_multiplier: f32,     // 1.5 = time-and-a-half
+    #[serde(default)]
+    pub ignore_switches: bool,    // no penalty for changing answers
+    #[serde(default)]
+    pub screen_reader: bool,      // focus changes are expected with assistive tech
+}
+
+/// What an accommodation changed for one evaluation, returned in the result.
+#[derive(Debug, Serialize, Clone)]
+pub struct AccommodationAdjustment {
+    pub time_multiplier: f32,
+    pub switch_penalties_waived: bool,
+    pub screen_reader_mode: bool,
+    pub suppressed_detectors: Vec<&'static str>,
+}
+
+fn default_time_multiplier() -> f32 {
+    1.0
+}
+
+impl Default for Accommodation {
+    fn default() -> Self {
+        Accommodation {
+            time_multiplier: 1.0,
+            ignore_switches: false,
+            screen_reader: false,
+        }
+    }
+}
+
+impl Accommodation {
+    pub fn sanitized(mut self) -> Self {
+        self.time_multiplier = if self.time_multiplier.is_finite() {
+            self.time_multiplier.clamp(1.0, MAX_TIME_MULTIPLIER)
+        } else {
+            1.0
+        };
+        self
+    }
+
+    pub fn is_standard(&self) -> bool {
+        *self == Accommodation::default()
+    }
+
+    pub fn scale_expected_time(&self, expected_time: u32) -> u32 {
+        (expected_time as f32 * self.time_multiplier).round() as u32
+    }
+
+    /// Maps an observed answer time back onto the standard timescale, for
+    /// item statistics. Detectors see observed times; see `time_floor`.
+    pub fn normalize_time(&self, time_seconds: u32) -> u32 {
+        (time_seconds as f32 / self.time_multiplier).round() as u32
+    }
+
+    /// A minimum answer time for this candidate. Floors only ever loosen
+    /// with extra time, so an accommodation never makes a timing detector
+    /// or a min-time rule stricter than for anyone else.
+    pub fn time_floor(&self, standard_seconds: f32) -> f32 {
+        standard_seconds / self.time_multiplier.max(1.0)
+    }
+
+    pub fn adjustment(&self) -> Option<AccommodationAdjustment> {
+        if self.is_standard() {
+            return None;
+        }
+
+        let mut suppressed_detectors = Vec::new();
+        if self.ignore_switches {
+            suppressed_detectors.push("excessive_switching");
+        }
+        if self.screen_reader {
+            suppressed_detectors.push("focus_loss");
+        }
+
+        Some(AccommodationAdjustment {
+            time_multiplier: self.time_multiplier,
+            switch_penalties_waived: self.ignore_switches,
+            screen_reader_mode: self.screen_reader,
+            suppressed_detectors,
+        })
+    }
+}
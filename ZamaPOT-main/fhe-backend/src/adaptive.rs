@@ -0,0 +1,110 @@
+use serde::{Deserialize, Serialize};
+use crate::irt::{self, AbilityEstimate, ItemParameters};
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy)]
+pub struct AdaptiveConfig {
+    pub target_standard_error: f64, // stop once the estimate is this precise
+    pub min_items: usize,
+    pub max_items: usize,
+}
+
+/// Adaptive progress for a session: one answer per served question.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct AdaptiveState {
+    pub config: AdaptiveConfig,
+    pub encrypted_answers: Vec<String>,
+    pub responses: Vec<f32>,
+    pub estimate: AbilityEstimate,
+    pub finished: bool,
+}
+
+impl Default for AdaptiveConfig {
+    fn default() -> Self {
+        AdaptiveConfig {
+            target_standard_error: 0.55,
+            min_items: 3,
+            max_items: 15,
+        }
+    }
+}
+
+impl AdaptiveState {
+    pub fn new(config: AdaptiveConfig, starting_ability: f64) -> Self {
+        AdaptiveState {
+            config,
+            encrypted_answers: Vec::new(),
+            responses: Vec::new(),
+            estimate: AbilityEstimate {
+                theta: starting_ability,
+                standard_error: irt::standard_error(&[], starting_ability),
+            },
+            finished: false,
+        }
+    }
+
+    /// Records the answer to the most recently served item and re-estimates
+    /// ability over everything answered so far.
+    pub fn record(&mut self, items: &[ItemParameters], encrypted_answer: String, credit: f32) {
+        self.encrypted_answers.push(encrypted_answer);
+        self.responses.push(credit);
+        self.estimate = irt::estimate_ability(&items[..self.responses.len()], &self.responses);
+
+        let answered = self.responses.len();
+        let precise = self.estimate.standard_error <= self.config.target_standard_error;
+        self.finished = answered >= self.config.max_items || (answered >= self.config.min_items && precise);
+    }
+
+    /// Under the Rasch model an item is most informative when its
+    /// difficulty matches the current ability estimate.
+    pub fn next_difficulty(&self) -> f32 {
+        irt::unit_difficulty(self.estimate.theta)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn items(count: usize) -> Vec<ItemParameters> {
+        vec![ItemParameters { discrimination: 1.0, difficulty: 0.0 }; count]
+    }
+
+    #[test]
+    fn not_finished_before_min_items_even_when_precise() {
+        let config = AdaptiveConfig { target_standard_error: 10.0, min_items: 3, max_items: 15 };
+        let mut state = AdaptiveState::new(config, 0.0);
+        state.record(&items(3), "a".to_string(), 1.0);
+        state.record(&items(3), "b".to_string(), 1.0);
+        assert!(!state.finished);
+        state.record(&items(3), "c".to_string(), 0.0);
+        assert!(state.finished);
+        assert_eq!(state.encrypted_answers, ["a", "b", "c"]);
+    }
+
+    #[test]
+    fn stops_at_max_items_or_target_precision() {
+        let config = AdaptiveConfig { target_standard_error: 0.0, min_items: 1, max_items: 4 };
+        let mut state = AdaptiveState::new(config, 0.0);
+        for i in 0..4 {
+            assert!(!state.finished);
+            state.record(&items(4), i.to_string(), (i % 2) as f32);
+        }
+        assert!(state.finished);
+
+        let mut state = AdaptiveState::new(AdaptiveConfig::default(), 0.0);
+        let pool = items(15);
+        while !state.finished {
+            state.record(&pool, String::new(), (state.responses.len() % 2) as f32);
+        }
+        assert!(state.estimate.standard_error <= AdaptiveConfig::default().target_standard_error);
+        assert!(state.responses.len() < 15);
+    }
+
+    #[test]
+    fn next_item_follows_the_estimate() {
+        let mut state = AdaptiveState::new(AdaptiveConfig::default(), 0.0);
+        assert_eq!(state.next_difficulty(), 0.5);
+        state.record(&items(1), String::new(), 1.0);
+        assert!(state.next_difficulty() > 0.5);
+    }
+}
@@ -0,0 +1,74 @@
+use serde::{Deserialize, Serialize};
+
+// Shorter submissions can land on a pattern by chance too easily
+const MIN_PATTERN_LENGTH: usize = 3;
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(tag = "pattern", rename_all = "snake_case")]
+pub enum AnswerPattern {
+    SameIndex { index: u8 },
+    Cyclic { period: usize },
+}
+
+/// Flags option sequences a script would produce: every answer at the same
+/// position, or a short cycle of distinct positions repeated at least twice.
+/// Unknown selections are skipped, so a partly known submission is judged
+/// on the positions that are known.
+pub fn detect_degenerate_pattern(selections: &[Option<u8>]) -> Option<AnswerPattern> {
+    let known: Vec<(usize, u8)> = selections.iter().enumerate()
+        .filter_map(|(i, s)| s.map(|s| (i, s)))
+        .collect();
+    if known.len() < MIN_PATTERN_LENGTH {
+        return None;
+    }
+
+    let first = known[0].1;
+    if known.iter().all(|&(_, s)| s == first) {
+        return Some(AnswerPattern::SameIndex { index: first });
+    }
+
+    for period in 2..=known.len() / 2 {
+        // Each position in the cycle takes the first known answer at it
+        let cycle: Option<Vec<u8>> = (0..period)
+            .map(|k| known.iter().find(|&&(i, _)| i % period == k).map(|&(_, s)| s))
+            .collect();
+        let Some(cycle) = cycle else { continue };
+        let distinct = cycle.iter().enumerate().all(|(i, s)| !cycle[..i].contains(s));
+        let repeats = known.iter().all(|&(i, s)| s == cycle[i % period]);
+        if distinct && repeats {
+            return Some(AnswerPattern::Cyclic { period });
+        }
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn flags_same_index_and_cycles() {
+        let all = |s: &[u8]| s.iter().map(|&s| Some(s)).collect::<Vec<_>>();
+        assert_eq!(detect_degenerate_pattern(&all(&[2, 2, 2, 2])), Some(AnswerPattern::SameIndex { index: 2 }));
+        assert_eq!(detect_degenerate_pattern(&all(&[0, 1, 0, 1, 0])), Some(AnswerPattern::Cyclic { period: 2 }));
+        assert_eq!(detect_degenerate_pattern(&all(&[0, 1, 2, 0, 1, 2])), Some(AnswerPattern::Cyclic { period: 3 }));
+        assert_eq!(detect_degenerate_pattern(&all(&[0, 2, 1, 3, 1, 0])), None);
+        assert_eq!(detect_degenerate_pattern(&all(&[1, 1])), None);
+    }
+
+    #[test]
+    fn judges_partly_known_submissions_on_known_positions() {
+        assert_eq!(
+            detect_degenerate_pattern(&[Some(3), None, Some(3), None, Some(3)]),
+            Some(AnswerPattern::SameIndex { index: 3 }),
+        );
+        assert_eq!(
+            detect_degenerate_pattern(&[Some(0), Some(1), None, Some(1), Some(0), Some(1)]),
+            Some(AnswerPattern::Cyclic { period: 2 }),
+        );
+        // A gap can't be read as a cycle the known answers break
+        assert_eq!(detect_degenerate_pattern(&[Some(0), Some(1), None, Some(0), Some(1)]), None);
+        assert_eq!(detect_degenerate_pattern(&[Some(1), None, None, Some(1)]), None);
+    }
+}
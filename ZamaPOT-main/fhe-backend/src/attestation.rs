@@ -0,0 +1,466 @@
+use k256::ecdsa::SigningKey;
+use serde::{Deserialize, Serialize};
+use sha3::{Digest, Keccak256};
+use std::path::Path;
+use crate::certificates;
+
+// EIP-712 domain of the passport contract (ERC721 name "TalentPassport")
+pub const DOMAIN_NAME: &str = "TalentPassport";
+pub const DOMAIN_VERSION: &str = "1";
+// Sepolia, where the contracts are deployed by default
+pub const DEFAULT_CHAIN_ID: u64 = 11_155_111;
+
+const MINT_SIGNATURE: &str =
+    "mintTalentBadge(string,bytes32,uint8,string,uint8,bool,uint256,uint256)";
+const TALENT_RECORDS_SIGNATURE: &str = "talentRecords(uint256)";
+const TRANSFER_SIGNATURE: &str = "transferFrom(address,address,uint256)";
+pub const BADGE_MINTED_EVENT: &str =
+    "BadgeMinted(address,uint256,string,uint8,string,uint8,bool,uint256)";
+const DOMAIN_TYPE: &str =
+    "EIP712Domain(string name,string version,uint256 chainId,address verifyingContract)";
+const BADGE_TYPE: &str = "TalentBadge(string skillType,bytes32 encryptedScore,uint8 level,\
+    string certificateId,uint8 cheatingLikelihood,bool behaviorFlagged,\
+    uint256 totalQuestions,uint256 correctAnswers)";
+
+/// Arguments of `EnhancedTalentPassport.mintTalentBadge`, already in the
+/// contract's types. Byte values are 0x-prefixed hex.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct MintArgs {
+    pub skill_type: String,
+    pub encrypted_score: String, // bytes32
+    pub level: u8,
+    pub certificate_id: String,
+    pub cheating_likelihood: u8, // 0-100
+    pub behavior_flagged: bool,
+    pub total_questions: u64,    // uint256
+    pub correct_answers: u64,    // uint256
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct MintDomain {
+    pub name: String,
+    pub version: String,
+    pub chain_id: u64,
+    pub verifying_contract: String, // 0x-prefixed address
+}
+
+/// Everything needed to submit a mint, signed by the oracle key.
+#[derive(Debug, Serialize, Clone)]
+pub struct MintAttestation {
+    pub args: MintArgs,
+    pub abi_types: [&'static str; 8],
+    pub calldata: String,   // ABI-encoded mintTalentBadge call
+    pub domain: MintDomain,
+    pub digest: String,     // EIP-712 hash that was signed
+    pub oracle: String,     // signer address
+    pub signature: String,  // 65 bytes r || s || v, v in {27, 28}
+}
+
+/// A decoded `BadgeMinted` event.
+#[derive(Debug, Serialize, Clone, PartialEq)]
+pub struct BadgeMinted {
+    pub user: String,
+    pub token_id: u64,
+    pub skill_type: String,
+    pub level: u8,
+    pub certificate_id: String,
+    pub cheating_likelihood: u8,
+    pub behavior_flagged: bool,
+    pub timestamp: u64,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct OracleInfo {
+    pub address: String,
+    pub domain: MintDomain,
+    pub domain_separator: String,
+    pub badge_type: &'static str,
+}
+
+pub fn keccak256(data: &[u8]) -> [u8; 32] {
+    Keccak256::digest(data).into()
+}
+
+pub fn to_hex(bytes: &[u8]) -> String {
+    format!("0x{}", hex::encode(bytes))
+}
+
+/// Parses a 0x-prefixed 20-byte address.
+pub fn parse_address(address: &str) -> Option<[u8; 20]> {
+    hex::decode(address.strip_prefix("0x")?).ok()?.try_into().ok()
+}
+
+/// Unique bytes32 the contract records in `usedScores`, binding the
+/// certificate id to the backend's score commitment.
+pub fn score_commitment(certificate_id: &str, score_commitment: &str) -> [u8; 32] {
+    let mut data = b"ppot-score".to_vec();
+    data.extend_from_slice(certificate_id.as_bytes());
+    data.push(0);
+    data.extend_from_slice(score_commitment.as_bytes());
+    keccak256(&data)
+}
+
+fn word_u64(value: u64) -> [u8; 32] {
+    let mut word = [0u8; 32];
+    word[24..].copy_from_slice(&value.to_be_bytes());
+    word
+}
+
+fn word_address(address: &[u8; 20]) -> [u8; 32] {
+    let mut word = [0u8; 32];
+    word[12..].copy_from_slice(address);
+    word
+}
+
+/// ERC721 `transferFrom` call, used to hand a relayed badge to its owner.
+pub fn transfer_calldata(from: &[u8; 20], to: &[u8; 20], token_id: u64) -> Vec<u8> {
+    let mut data = keccak256(TRANSFER_SIGNATURE.as_bytes())[..4].to_vec();
+    data.extend_from_slice(&word_address(from));
+    data.extend_from_slice(&word_address(to));
+    data.extend_from_slice(&word_u64(token_id));
+    data
+}
+
+pub fn badge_minted_topic() -> String {
+    to_hex(&keccak256(BADGE_MINTED_EVENT.as_bytes()))
+}
+
+/// `talentRecords(tokenId)` getter call.
+pub fn talent_record_calldata(token_id: u64) -> Vec<u8> {
+    let mut data = keccak256(TALENT_RECORDS_SIGNATURE.as_bytes())[..4].to_vec();
+    data.extend_from_slice(&word_u64(token_id));
+    data
+}
+
+/// The `encryptedScore` field of a `talentRecords` return value: the
+/// second head word, after the offset of `skillType`.
+pub fn decode_record_score(data: &[u8]) -> Option<[u8; 32]> {
+    data.get(32..64)?.try_into().ok()
+}
+
+fn read_word(data: &[u8], index: usize) -> Option<&[u8]> {
+    data.get(index * 32..(index + 1) * 32)
+}
+
+/// Reads a small integer word; None if it does not fit in a u64.
+fn read_u64(data: &[u8], index: usize) -> Option<u64> {
+    let word = read_word(data, index)?;
+    if word[..24].iter().any(|&b| b != 0) {
+        return None;
+    }
+    Some(u64::from_be_bytes(word[24..].try_into().ok()?))
+}
+
+fn read_string(data: &[u8], head_index: usize) -> Option<String> {
+    let offset = read_u64(data, head_index)? as usize;
+    let length = read_u64(data.get(offset..)?, 0)? as usize;
+    let bytes = data.get(offset + 32..offset + 32 + length)?;
+    String::from_utf8(bytes.to_vec()).ok()
+}
+
+impl BadgeMinted {
+    /// Decodes a log given its topics (signature, indexed user) and data.
+    pub fn decode(topics: &[[u8; 32]], data: &[u8]) -> Option<Self> {
+        if topics.len() != 2 || to_hex(&topics[0]) != badge_minted_topic() {
+            return None;
+        }
+        Some(BadgeMinted {
+            user: to_hex(&topics[1][12..]),
+            token_id: read_u64(data, 0)?,
+            skill_type: read_string(data, 1)?,
+            level: read_u64(data, 2)?.try_into().ok()?,
+            certificate_id: read_string(data, 3)?,
+            cheating_likelihood: read_u64(data, 4)?.try_into().ok()?,
+            behavior_flagged: read_u64(data, 5)? != 0,
+            timestamp: read_u64(data, 6)?,
+        })
+    }
+}
+
+fn encode_string(text: &str) -> Vec<u8> {
+    let mut encoded = word_u64(text.len() as u64).to_vec();
+    encoded.extend_from_slice(text.as_bytes());
+    encoded.resize(32 + text.len().div_ceil(32) * 32, 0);
+    encoded
+}
+
+impl MintArgs {
+    fn score_bytes(&self) -> [u8; 32] {
+        hex::decode(self.encrypted_score.trim_start_matches("0x")).ok()
+            .and_then(|bytes| bytes.try_into().ok())
+            .unwrap_or([0u8; 32])
+    }
+
+    /// Solidity ABI encoding of the call, selector included.
+    pub fn calldata(&self) -> Vec<u8> {
+        let skill_type = encode_string(&self.skill_type);
+        let certificate_id = encode_string(&self.certificate_id);
+        let head_size = 8 * 32;
+
+        let mut data = keccak256(MINT_SIGNATURE.as_bytes())[..4].to_vec();
+        data.extend_from_slice(&word_u64(head_size as u64));
+        data.extend_from_slice(&self.score_bytes());
+        data.extend_from_slice(&word_u64(self.level as u64));
+        data.extend_from_slice(&word_u64((head_size + skill_type.len()) as u64));
+        data.extend_from_slice(&word_u64(self.cheating_likelihood as u64));
+        data.extend_from_slice(&word_u64(self.behavior_flagged as u64));
+        data.extend_from_slice(&word_u64(self.total_questions));
+        data.extend_from_slice(&word_u64(self.correct_answers));
+        data.extend_from_slice(&skill_type);
+        data.extend_from_slice(&certificate_id);
+        data
+    }
+
+    /// EIP-712 `hashStruct` of the TalentBadge message.
+    pub fn struct_hash(&self) -> [u8; 32] {
+        let mut data = keccak256(BADGE_TYPE.as_bytes()).to_vec();
+        data.extend_from_slice(&keccak256(self.skill_type.as_bytes()));
+        data.extend_from_slice(&self.score_bytes());
+        data.extend_from_slice(&word_u64(self.level as u64));
+        data.extend_from_slice(&keccak256(self.certificate_id.as_bytes()));
+        data.extend_from_slice(&word_u64(self.cheating_likelihood as u64));
+        data.extend_from_slice(&word_u64(self.behavior_flagged as u64));
+        data.extend_from_slice(&word_u64(self.total_questions));
+        data.extend_from_slice(&word_u64(self.correct_answers));
+        keccak256(&data)
+    }
+}
+
+impl MintDomain {
+    pub fn new(chain_id: u64, verifying_contract: [u8; 20]) -> Self {
+        MintDomain {
+            name: DOMAIN_NAME.to_string(),
+            version: DOMAIN_VERSION.to_string(),
+            chain_id,
+            verifying_contract: to_hex(&verifying_contract),
+        }
+    }
+
+    pub fn separator(&self) -> [u8; 32] {
+        let contract = parse_address(&self.verifying_contract).unwrap_or([0u8; 20]);
+        let mut data = keccak256(DOMAIN_TYPE.as_bytes()).to_vec();
+        data.extend_from_slice(&keccak256(self.name.as_bytes()));
+        data.extend_from_slice(&keccak256(self.version.as_bytes()));
+        data.extend_from_slice(&word_u64(self.chain_id));
+        data.extend_from_slice(&word_address(&contract));
+        keccak256(&data)
+    }
+
+    /// `keccak256("\x19\x01" || domainSeparator || hashStruct(message))`
+    pub fn digest(&self, args: &MintArgs) -> [u8; 32] {
+        let mut data = vec![0x19, 0x01];
+        data.extend_from_slice(&self.separator());
+        data.extend_from_slice(&args.struct_hash());
+        keccak256(&data)
+    }
+}
+
+/// The secp256k1 key that attests mints came from a real evaluation.
+pub struct OracleKey {
+    signing_key: SigningKey,
+    domain: MintDomain,
+}
+
+impl OracleKey {
+    pub fn generate(domain: MintDomain) -> Self {
+        OracleKey {
+            signing_key: SigningKey::random(&mut rand::rngs::OsRng),
+            domain,
+        }
+    }
+
+    /// Loads a key from a file holding the 32-byte secret as hex.
+    pub fn load(path: &Path, domain: MintDomain) -> std::io::Result<Self> {
+        let contents = certificates::read_secret_file(path)?;
+        let signing_key = hex::decode(contents.trim().trim_start_matches("0x")).ok()
+            .and_then(|bytes| SigningKey::from_slice(&bytes).ok())
+            .ok_or_else(|| std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                "oracle key must be a 32-byte hex-encoded secp256k1 secret",
+            ))?;
+        Ok(OracleKey { signing_key, domain })
+    }
+
+    pub fn save(&self, path: &Path) -> std::io::Result<()> {
+        certificates::write_secret_file(path, &hex::encode(self.signing_key.to_bytes()))
+    }
+
+    /// Ethereum address: last 20 bytes of the keccak of the uncompressed public key.
+    pub fn address(&self) -> String {
+        let point = self.signing_key.verifying_key().to_encoded_point(false);
+        to_hex(&keccak256(&point.as_bytes()[1..])[12..])
+    }
+
+    pub fn info(&self) -> OracleInfo {
+        OracleInfo {
+            address: self.address(),
+            domain: self.domain.clone(),
+            domain_separator: to_hex(&self.domain.separator()),
+            badge_type: BADGE_TYPE,
+        }
+    }
+
+    pub fn attest(&self, args: MintArgs) -> MintAttestation {
+        let digest = self.domain.digest(&args);
+        let (signature, recovery_id) = self.signing_key.sign_prehash_recoverable(&digest)
+            .expect("signing a 32-byte digest cannot fail");
+        let mut signature_bytes = signature.to_bytes().to_vec();
+        signature_bytes.push(27 + recovery_id.to_byte());
+
+        MintAttestation {
+            abi_types: ["string", "bytes32", "uint8", "string", "uint8", "bool", "uint256", "uint256"],
+            calldata: to_hex(&args.calldata()),
+            domain: self.domain.clone(),
+            digest: to_hex(&digest),
+            oracle: self.address(),
+            signature: to_hex(&signature_bytes),
+            args,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use k256::ecdsa::{RecoveryId, Signature, VerifyingKey};
+
+    // Hardhat's first deployment address
+    const CONTRACT: &str = "0x5fbdb2315678afecb367f032d93f642f64180aa3";
+
+    fn args() -> MintArgs {
+        MintArgs {
+            skill_type: "math".to_string(),
+            encrypted_score: to_hex(&[0x11; 32]),
+            level: 4,
+            certificate_id: "CERT_00112233445566778899aabbccddeeff".to_string(),
+            cheating_likelihood: 12,
+            behavior_flagged: false,
+            total_questions: 5,
+            correct_answers: 4,
+        }
+    }
+
+    fn oracle() -> OracleKey {
+        // The EIP-155 example key
+        OracleKey {
+            signing_key: SigningKey::from_slice(&[0x46; 32]).unwrap(),
+            domain: MintDomain::new(31_337, parse_address(CONTRACT).unwrap()),
+        }
+    }
+
+    fn recover(digest: &[u8], signature: &str) -> String {
+        let bytes = hex::decode(signature.trim_start_matches("0x")).unwrap();
+        let recovery_id = RecoveryId::from_byte(bytes[64] - 27).unwrap();
+        let signature = Signature::from_slice(&bytes[..64]).unwrap();
+        let key = VerifyingKey::recover_from_prehash(digest, &signature, recovery_id).unwrap();
+        to_hex(&keccak256(&key.to_encoded_point(false).as_bytes()[1..])[12..])
+    }
+
+    #[test]
+    fn keccak_and_address_match_known_vectors() {
+        assert_eq!(
+            hex::encode(keccak256(b"")),
+            "c5d2460186f7233c927e7db2dcc703c0e500b653ca82273b7bfad8045d85a470",
+        );
+        assert_eq!(oracle().address(), "0x9d8a62f656a8d1615c1294fd71e9cfb3e4855a4f");
+    }
+
+    #[test]
+    fn eip712_digest_matches_reference() {
+        let oracle = oracle();
+        assert_eq!(
+            hex::encode(oracle.domain.separator()),
+            "08e079431c7880d66734c27a830c5dab08396aff1a8f173cbcb5afa46e0dc673",
+        );
+        assert_eq!(
+            hex::encode(oracle.domain.digest(&args())),
+            "ede7452935f77af16e3e5c092d6870035d013c9851bca67ca03fa9dfd5f83e76",
+        );
+
+        let mut raised = args();
+        raised.level = 5;
+        assert_ne!(oracle.domain.digest(&raised), oracle.domain.digest(&args()));
+        let other_chain = MintDomain::new(DEFAULT_CHAIN_ID, parse_address(CONTRACT).unwrap());
+        assert_ne!(other_chain.digest(&args()), oracle.domain.digest(&args()));
+    }
+
+    #[test]
+    fn attestation_recovers_to_the_oracle() {
+        let oracle = oracle();
+        let attestation = oracle.attest(args());
+        assert_eq!(attestation.digest, to_hex(&oracle.domain.digest(&args())));
+        assert_eq!(attestation.signature.len(), 2 + 65 * 2);
+        assert_eq!(recover(&oracle.domain.digest(&args()), &attestation.signature), oracle.address());
+
+        // The same signature over edited arguments names some other signer
+        let mut flagged = args();
+        flagged.behavior_flagged = true;
+        assert_ne!(recover(&oracle.domain.digest(&flagged), &attestation.signature), oracle.address());
+    }
+
+    #[test]
+    fn calldata_follows_the_abi_layout() {
+        let data = args().calldata();
+        assert_eq!(hex::encode(&data[..4]), "591f7d39");
+        let body = &data[4..];
+        // Eight head words, then each string's length word and padded bytes
+        assert_eq!(body.len(), (8 + (1 + 1) + (1 + 2)) * 32);
+        assert_eq!(read_string(body, 0).as_deref(), Some("math"));
+        assert_eq!(read_word(body, 1), Some(&[0x11; 32][..]));
+        assert_eq!(read_u64(body, 2), Some(4));
+        assert_eq!(read_string(body, 3).as_deref(), Some("CERT_00112233445566778899aabbccddeeff"));
+        assert_eq!(read_u64(body, 4), Some(12));
+        assert_eq!(read_u64(body, 5), Some(0));
+        assert_eq!(read_u64(body, 6), Some(5));
+        assert_eq!(read_u64(body, 7), Some(4));
+    }
+
+    #[test]
+    fn transfer_and_event_round_trip() {
+        let from = [0xaa; 20];
+        let to = [0xbb; 20];
+        let data = transfer_calldata(&from, &to, 7);
+        assert_eq!(hex::encode(&data[..4]), "23b872dd");
+        assert_eq!(&data[4 + 12..4 + 32], &from);
+        assert_eq!(read_u64(&data[4..], 2), Some(7));
+
+        let mut user = [0u8; 32];
+        user[12..].copy_from_slice(&to);
+        let topic: [u8; 32] = keccak256(BADGE_MINTED_EVENT.as_bytes());
+        let mut log = word_u64(7).to_vec();
+        log.extend_from_slice(&word_u64(7 * 32));
+        log.extend_from_slice(&word_u64(4));
+        log.extend_from_slice(&word_u64(9 * 32));
+        log.extend_from_slice(&word_u64(12));
+        log.extend_from_slice(&word_u64(1));
+        log.extend_from_slice(&word_u64(1_700_000_000));
+        log.extend_from_slice(&encode_string("math"));
+        log.extend_from_slice(&encode_string("CERT_1"));
+
+        let event = BadgeMinted::decode(&[topic, user], &log).unwrap();
+        assert_eq!(event.user, to_hex(&to));
+        assert_eq!((event.token_id, event.level, event.cheating_likelihood), (7, 4, 12));
+        assert_eq!((event.skill_type.as_str(), event.certificate_id.as_str()), ("math", "CERT_1"));
+        assert!(event.behavior_flagged);
+        assert_eq!(BadgeMinted::decode(&[user, user], &log), None);
+        assert_eq!(BadgeMinted::decode(&[topic, user], &log[..log.len() - 32]), None);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn oracle_key_file_is_private() {
+        use std::os::unix::fs::PermissionsExt;
+        let domain = MintDomain::new(31_337, parse_address(CONTRACT).unwrap());
+        let key = OracleKey::generate(domain.clone());
+        let path = std::env::temp_dir().join(format!("ppot-oracle-{}.key", key.address()));
+        key.save(&path).unwrap();
+
+        assert_eq!(std::fs::metadata(&path).unwrap().permissions().mode() & 0o777, 0o600);
+        assert_eq!(OracleKey::load(&path, domain.clone()).unwrap().address(), key.address());
+
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o640)).unwrap();
+        let error = OracleKey::load(&path, domain).err().unwrap();
+        assert_eq!(error.kind(), std::io::ErrorKind::PermissionDenied);
+        std::fs::remove_file(&path).unwrap();
+    }
+}
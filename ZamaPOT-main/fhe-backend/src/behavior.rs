@@ -0,0 +1,60 @@
+use serde::{Deserialize, Serialize};
+use crate::answer_patterns::{self, AnswerPattern};
+use crate::input_dynamics::InputSample;
+use crate::telemetry::{self, EventStream, TelemetrySummary};
+
+// Longest time credited to one question; longer reports are clamped
+pub const MAX_ANSWER_SECONDS: u32 = 3_600;
+// Most answer changes counted for one question
+pub const MAX_SWITCHES_PER_QUESTION: u32 = 100;
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct BehaviorData {
+    pub answer_times: Vec<u32>, // Time spent on each question in seconds
+    pub switch_counts: Vec<u32>, // How many times each answer was changed
+    pub start_time: u64,
+    pub end_time: u64,
+    #[serde(default)]
+    pub events: Vec<serde_json::Value>, // typed client events, parsed and bounded server-side
+    #[serde(default)]
+    pub input: InputSample, // keystroke and pointer summaries for free-response items
+}
+
+/// What the server derives from a submission's raw client data.
+#[derive(Debug)]
+pub struct ObservedBehavior {
+    pub events: EventStream,
+    pub telemetry: TelemetrySummary,
+    pub answer_pattern: Option<AnswerPattern>,
+}
+
+impl BehaviorData {
+    /// Clamps per-question times and switch counts so totals stay meaningful.
+    pub fn sanitized(mut self) -> Self {
+        for time in &mut self.answer_times {
+            *time = (*time).min(MAX_ANSWER_SECONDS);
+        }
+        for switches in &mut self.switch_counts {
+            *switches = (*switches).min(MAX_SWITCHES_PER_QUESTION);
+        }
+        self
+    }
+
+    /// `submitted` holds the answers the server could decrypt, which today
+    /// means sim-xor envelopes only. They take precedence over
+    /// client-reported selections; for tfhe-uint8 and legacy answers the
+    /// check has only what the client reports, so it covers those items
+    /// only as far as the client is honest.
+    pub fn observe(&self, question_count: usize, submitted: &[Option<u8>]) -> ObservedBehavior {
+        let events = telemetry::parse_events(&self.events, self.start_time, self.end_time);
+        let telemetry = telemetry::summarize(&events, self.start_time, self.end_time);
+        let selections = telemetry::final_selections(&events, question_count, submitted);
+        let answer_pattern = answer_patterns::detect_degenerate_pattern(&selections);
+
+        ObservedBehavior {
+            events,
+            telemetry,
+            answer_pattern,
+        }
+    }
+}
@@ -0,0 +1,301 @@
+// Offline calibration for the cheating detectors.
+//
+// Usage: calibrate <sessions.jsonl> [--out <model.json>] [--holdout <fraction>]
+//
+// Each input line is a labeled past session:
+//   {"behavior_data": {...}, "label": "honest" | "cheating",
+//    "question_count": 3, "quiz_type": "math", "consistency_score": 0.9,
+//    "accommodation": {"time_multiplier": 1.5}}
+// Only `behavior_data` and `label` are required. Weights and threshold are
+// tuned on a training split and reported on the held-out rest (a quarter of
+// each label by default). The tuned model is written as JSON and can be
+// loaded by the server through CHEATING_MODEL_PATH.
+
+use fhe_backend::accommodations::Accommodation;
+use fhe_backend::behavior::BehaviorData;
+use fhe_backend::cheating_model::{
+    CheatingModel, DetectorSignals, DetectorWeights, DETECTOR_COUNT, DETECTOR_NAMES,
+};
+use fhe_backend::dynamic_questions::UserSession;
+use serde::Deserialize;
+use std::io::{BufRead, BufReader};
+use std::path::PathBuf;
+
+// Share of each label held out for evaluation
+const DEFAULT_HOLDOUT: f32 = 0.25;
+
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+enum Label {
+    Honest,
+    Cheating,
+}
+
+#[derive(Debug, Deserialize)]
+struct LabeledSession {
+    behavior_data: BehaviorData,
+    label: Label,
+    #[serde(default)]
+    question_count: Option<usize>,
+    #[serde(default)]
+    quiz_type: Option<String>,
+    #[serde(default)]
+    consistency_score: Option<f32>,
+    #[serde(default)]
+    accommodation: Option<Accommodation>,
+}
+
+struct Sample {
+    signals: DetectorSignals,
+    cheating: bool,
+}
+
+#[derive(Debug, Default)]
+struct Confusion {
+    tp: usize,
+    fp: usize,
+    tn: usize,
+    fn_: usize,
+}
+
+impl Confusion {
+    fn precision(&self) -> f32 {
+        ratio(self.tp, self.tp + self.fp)
+    }
+
+    fn recall(&self) -> f32 {
+        ratio(self.tp, self.tp + self.fn_)
+    }
+
+    fn false_positive_rate(&self) -> f32 {
+        ratio(self.fp, self.fp + self.tn)
+    }
+
+    fn f1(&self) -> f32 {
+        let (p, r) = (self.precision(), self.recall());
+        if p + r == 0.0 { 0.0 } else { 2.0 * p * r / (p + r) }
+    }
+}
+
+fn ratio(num: usize, den: usize) -> f32 {
+    if den == 0 { 0.0 } else { num as f32 / den as f32 }
+}
+
+fn replay(session: &LabeledSession) -> Sample {
+    let data = &session.behavior_data;
+    let question_count = session.question_count.unwrap_or(data.answer_times.len()).max(1);
+    let quiz_type = session.quiz_type.as_deref().unwrap_or("math");
+
+    let accommodation = session.accommodation.clone().unwrap_or_default().sanitized();
+    let mut user_session = UserSession::new("calibration".to_string(), quiz_type, question_count)
+        .with_accommodation(accommodation);
+    user_session.record_behavior(data, &data.observe(question_count, &[]));
+    user_session.record_consistency(session.consistency_score.unwrap_or(1.0));
+
+    Sample {
+        signals: user_session.detector_signals(),
+        cheating: session.label == Label::Cheating,
+    }
+}
+
+/// Splits samples into training and holdout sets. Each label's holdout
+/// share is taken evenly across the file, so both sets see early and late
+/// sessions and the split is the same on every run.
+fn split(samples: Vec<Sample>, holdout: f32) -> (Vec<Sample>, Vec<Sample>) {
+    let mut seen = [0usize; 2];
+    let (mut training, mut held_out) = (Vec::new(), Vec::new());
+    for sample in samples {
+        let n = &mut seen[sample.cheating as usize];
+        let held = ((*n + 1) as f32 * holdout).floor() > (*n as f32 * holdout).floor();
+        *n += 1;
+        if held {
+            held_out.push(sample);
+        } else {
+            training.push(sample);
+        }
+    }
+    (training, held_out)
+}
+
+fn has_both_labels(samples: &[Sample]) -> bool {
+    samples.iter().any(|s| s.cheating) && samples.iter().any(|s| !s.cheating)
+}
+
+fn confusion(model: &CheatingModel, samples: &[Sample]) -> Confusion {
+    let mut c = Confusion::default();
+    for sample in samples {
+        let flagged = model.is_flagged(model.likelihood(&sample.signals));
+        match (flagged, sample.cheating) {
+            (true, true) => c.tp += 1,
+            (true, false) => c.fp += 1,
+            (false, false) => c.tn += 1,
+            (false, true) => c.fn_ += 1,
+        }
+    }
+    c
+}
+
+/// Area under the ROC curve as the probability a cheating session scores
+/// above an honest one (ties count half).
+fn roc_auc(model: &CheatingModel, samples: &[Sample]) -> f32 {
+    let scores = |cheating: bool| -> Vec<f32> {
+        samples.iter()
+            .filter(|s| s.cheating == cheating)
+            .map(|s| model.likelihood(&s.signals))
+            .collect()
+    };
+    let (positives, negatives) = (scores(true), scores(false));
+    if positives.is_empty() || negatives.is_empty() {
+        return 0.0;
+    }
+
+    let mut wins = 0.0;
+    for p in &positives {
+        for n in &negatives {
+            wins += if p > n { 1.0 } else if p == n { 0.5 } else { 0.0 };
+        }
+    }
+    wins / (positives.len() * negatives.len()) as f32
+}
+
+fn roc_points(model: &CheatingModel, samples: &[Sample]) -> Vec<(f32, f32, f32)> {
+    (0..10)
+        .map(|step| {
+            let threshold = step as f32 / 10.0;
+            let probe = CheatingModel { flag_threshold: threshold, ..model.clone() };
+            let c = confusion(&probe, samples);
+            (threshold, c.false_positive_rate(), c.recall())
+        })
+        .collect()
+}
+
+/// Weights each detector by its log-likelihood ratio between cheating and
+/// honest sessions, normalized so that every useful detector firing sums to 1.
+/// Detectors that fire more often on honest sessions get no weight.
+fn tune_weights(samples: &[Sample]) -> [f32; DETECTOR_COUNT] {
+    let cheating = samples.iter().filter(|s| s.cheating).count();
+    let honest = samples.len() - cheating;
+
+    let mut weights = [0.0; DETECTOR_COUNT];
+    for (i, weight) in weights.iter_mut().enumerate() {
+        let fired = |label: bool| samples.iter().filter(|s| s.cheating == label && s.signals.0[i]).count();
+        // Add-one smoothing keeps rare detectors from getting infinite weight
+        let p_cheating = (fired(true) as f32 + 1.0) / (cheating as f32 + 2.0);
+        let p_honest = (fired(false) as f32 + 1.0) / (honest as f32 + 2.0);
+        *weight = (p_cheating / p_honest).ln().max(0.0);
+    }
+
+    let total: f32 = weights.iter().sum();
+    if total > 0.0 {
+        for weight in &mut weights {
+            *weight /= total;
+        }
+    }
+    weights
+}
+
+fn tune_threshold(weights: [f32; DETECTOR_COUNT], samples: &[Sample]) -> f32 {
+    let mut best = (0.0, -1.0);
+    for step in 0..20 {
+        let threshold = step as f32 * 0.05;
+        let model = CheatingModel { weights: DetectorWeights::from_array(weights), flag_threshold: threshold };
+        let f1 = confusion(&model, samples).f1();
+        if f1 >= best.1 {
+            best = (threshold, f1);
+        }
+    }
+    best.0
+}
+
+fn report(name: &str, model: &CheatingModel, samples: &[Sample]) {
+    let c = confusion(model, samples);
+    println!("== {} (flag threshold {:.2})", name, model.flag_threshold);
+    println!("   precision {:.3}  recall {:.3}  f1 {:.3}  fpr {:.3}", c.precision(), c.recall(), c.f1(), c.false_positive_rate());
+    println!("   tp {}  fp {}  tn {}  fn {}", c.tp, c.fp, c.tn, c.fn_);
+    println!("   ROC AUC {:.3}", roc_auc(model, samples));
+    println!("   threshold   fpr     tpr");
+    for (threshold, fpr, tpr) in roc_points(model, samples) {
+        println!("   {:>9.2}   {:.3}   {:.3}", threshold, fpr, tpr);
+    }
+}
+
+fn main() -> std::io::Result<()> {
+    let mut args = std::env::args().skip(1);
+    let mut dataset = None;
+    let mut out = PathBuf::from("cheating_model.json");
+    let mut holdout = Some(DEFAULT_HOLDOUT);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--out" => out = args.next().map(PathBuf::from).unwrap_or(out),
+            "--holdout" => holdout = args.next().and_then(|f| f.parse().ok()),
+            _ => dataset = Some(PathBuf::from(arg)),
+        }
+    }
+    let (Some(dataset), Some(holdout)) = (dataset, holdout.filter(|f| *f > 0.0 && *f < 1.0)) else {
+        eprintln!("Usage: calibrate <sessions.jsonl> [--out <model.json>] [--holdout <fraction between 0 and 1>]");
+        std::process::exit(2);
+    };
+
+    let mut samples = Vec::new();
+    let reader = BufReader::new(std::fs::File::open(&dataset)?);
+    for (line_number, line) in reader.lines().enumerate() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        match serde_json::from_str::<LabeledSession>(&line) {
+            Ok(session) => samples.push(replay(&session)),
+            Err(e) => eprintln!("⚠️  Skipping line {}: {}", line_number + 1, e),
+        }
+    }
+
+    let cheating = samples.iter().filter(|s| s.cheating).count();
+    println!("📊 Loaded {} sessions ({} cheating, {} honest)", samples.len(), cheating, samples.len() - cheating);
+    if cheating == 0 || cheating == samples.len() {
+        eprintln!("❌ Dataset needs both honest and cheating sessions");
+        std::process::exit(1);
+    }
+
+    let (samples, held_out) = split(samples, holdout);
+    println!("   Training on {}, holding out {}", samples.len(), held_out.len());
+    if !has_both_labels(&samples) || !has_both_labels(&held_out) {
+        eprintln!("❌ Training and holdout sets each need honest and cheating sessions; add data or change --holdout");
+        std::process::exit(1);
+    }
+    let cheating = samples.iter().filter(|s| s.cheating).count();
+
+    println!("\n   detector              honest   cheating   precision");
+    for (i, name) in DETECTOR_NAMES.iter().enumerate() {
+        let fired = |label: bool| samples.iter().filter(|s| s.cheating == label && s.signals.0[i]).count();
+        let (on_honest, on_cheating) = (fired(false), fired(true));
+        println!(
+            "   {:<20} {:>7.3}   {:>8.3}   {:>9.3}",
+            name,
+            ratio(on_honest, samples.len() - cheating),
+            ratio(on_cheating, cheating),
+            ratio(on_cheating, on_honest + on_cheating),
+        );
+    }
+    println!();
+
+    let current = CheatingModel::default();
+    report("Current model, holdout", &current, &held_out);
+
+    let weights = tune_weights(&samples);
+    let tuned = CheatingModel {
+        weights: DetectorWeights::from_array(weights),
+        flag_threshold: tune_threshold(weights, &samples),
+    };
+    println!();
+    report("Tuned model, training", &tuned, &samples);
+    println!();
+    report("Tuned model, holdout", &tuned, &held_out);
+
+    if let Err(e) = tuned.validate() {
+        eprintln!("❌ Tuned model is invalid: {}", e);
+        std::process::exit(1);
+    }
+    tuned.save(&out)?;
+    println!("\n✅ Wrote tuned model to {}", out.display());
+    Ok(())
+}
@@ -0,0 +1,314 @@
+use ed25519_dalek::{Signature, Signer, SigningKey, Verifier, VerifyingKey};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fmt;
+use std::io::Write;
+use std::path::Path;
+
+// Bumped whenever the canonical encoding changes
+pub const PAYLOAD_VERSION: u8 = 2;
+pub const SIGNATURE_ALGORITHM: &str = "Ed25519";
+
+/// Everything a certificate attests to. Signed over `canonical_bytes`,
+/// never over a JSON rendering, so verifiers don't depend on field order.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct CertificatePayload {
+    pub version: u8,
+    pub certificate_id: String,
+    pub quiz_type: String,
+    pub level: u8,
+    pub score_commitment: String,     // hex SHA-256 of the encrypted score
+    pub cheating_likelihood_bps: u16, // basis points, so the encoding has no floats
+    pub user_id: String,
+    pub issued_at: u64,               // unix seconds
+    #[serde(default)]
+    pub expires_at: Option<u64>,      // unix seconds, for quizzes that require recertification
+    pub session_hash: String,         // hex SHA-256 of the session and its answers
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct SignedCertificate {
+    pub payload: CertificatePayload,
+    pub key_id: String,
+    pub signature: String, // hex
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct IssuerPublicKey {
+    pub algorithm: &'static str,
+    pub key_id: String,
+    pub public_key: String, // hex
+    pub payload_version: u8,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum CertificateError {
+    UnknownKey(String),
+    MalformedSignature,
+    InvalidSignature,
+}
+
+impl fmt::Display for CertificateError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CertificateError::UnknownKey(id) => write!(f, "certificate was signed by unknown key {}", id),
+            CertificateError::MalformedSignature => write!(f, "signature is not 64 hex-encoded bytes"),
+            CertificateError::InvalidSignature => write!(f, "signature does not match the payload"),
+        }
+    }
+}
+
+pub fn sha256_hex(data: &[u8]) -> String {
+    hex::encode(Sha256::digest(data))
+}
+
+/// Binds a session id to the exact answers that were submitted.
+pub fn session_hash(session_id: &str, encrypted_answers: &[String]) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(session_id.as_bytes());
+    for answer in encrypted_answers {
+        hasher.update([0u8]);
+        hasher.update(answer.as_bytes());
+    }
+    hex::encode(hasher.finalize())
+}
+
+pub fn score_commitment(encrypted_score: &str) -> String {
+    sha256_hex(encrypted_score.as_bytes())
+}
+
+/// Escapes text for the newline-separated canonical encodings.
+pub(crate) fn escape_field(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('\n', "\\n").replace('\t', "\\t")
+}
+
+impl CertificatePayload {
+    /// Newline-separated fields in a fixed order. Text fields are escaped
+    /// so the encoding stays unambiguous.
+    pub fn canonical_bytes(&self) -> Vec<u8> {
+        format!(
+            "ppot-certificate-v{}\n{}\n{}\n{}\n{}\n{}\n{}\n{}\n{}\n{}",
+            self.version,
+            escape_field(&self.certificate_id),
+            escape_field(&self.quiz_type),
+            self.level,
+            self.score_commitment,
+            self.cheating_likelihood_bps,
+            escape_field(&self.user_id),
+            self.issued_at,
+            self.expires_at.map(|t| t.to_string()).unwrap_or_default(),
+            self.session_hash,
+        )
+        .into_bytes()
+    }
+}
+
+/// Reads a secret key file, refusing one that group or others can read.
+pub(crate) fn read_secret_file(path: &Path) -> std::io::Result<String> {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let mode = std::fs::metadata(path)?.permissions().mode();
+        if mode & 0o077 != 0 {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::PermissionDenied,
+                format!("{} is accessible by group or others (mode {:o}); chmod 600 it", path.display(), mode & 0o777),
+            ));
+        }
+    }
+    std::fs::read_to_string(path)
+}
+
+/// Creates a secret key file readable only by its owner. Never overwrites
+/// an existing file.
+pub(crate) fn write_secret_file(path: &Path, contents: &str) -> std::io::Result<()> {
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    options.open(path)?.write_all(contents.as_bytes())
+}
+
+/// The backend's certificate signing key.
+pub struct IssuerKey {
+    signing_key: SigningKey,
+}
+
+impl IssuerKey {
+    pub fn generate() -> Self {
+        IssuerKey {
+            signing_key: SigningKey::generate(&mut rand::rngs::OsRng),
+        }
+    }
+
+    /// Loads a key from a file holding the 32-byte secret seed as hex.
+    pub fn load(path: &Path) -> std::io::Result<Self> {
+        let contents = read_secret_file(path)?;
+        let seed: [u8; 32] = hex::decode(contents.trim()).ok()
+            .and_then(|bytes| bytes.try_into().ok())
+            .ok_or_else(|| std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                "issuer key must be 32 hex-encoded bytes",
+            ))?;
+        Ok(IssuerKey {
+            signing_key: SigningKey::from_bytes(&seed),
+        })
+    }
+
+    pub fn save(&self, path: &Path) -> std::io::Result<()> {
+        write_secret_file(path, &hex::encode(self.signing_key.to_bytes()))
+    }
+
+    pub fn verifying_key(&self) -> VerifyingKey {
+        self.signing_key.verifying_key()
+    }
+
+    pub fn key_id(&self) -> String {
+        key_id(&self.verifying_key())
+    }
+
+    pub fn public_key(&self) -> IssuerPublicKey {
+        IssuerPublicKey {
+            algorithm: SIGNATURE_ALGORITHM,
+            key_id: self.key_id(),
+            public_key: hex::encode(self.verifying_key().as_bytes()),
+            payload_version: PAYLOAD_VERSION,
+        }
+    }
+
+    /// Raw Ed25519 signature, for formats with their own encoding.
+    pub fn sign_raw(&self, bytes: &[u8]) -> [u8; 64] {
+        self.signing_key.sign(bytes).to_bytes()
+    }
+
+    /// Hex signature over arbitrary bytes, for the other signed documents.
+    pub fn sign_bytes(&self, bytes: &[u8]) -> String {
+        hex::encode(self.sign_raw(bytes))
+    }
+
+    pub fn sign(&self, payload: CertificatePayload) -> SignedCertificate {
+        SignedCertificate {
+            signature: self.sign_bytes(&payload.canonical_bytes()),
+            key_id: self.key_id(),
+            payload,
+        }
+    }
+}
+
+/// First 8 bytes of the SHA-256 of the public key.
+pub fn key_id(key: &VerifyingKey) -> String {
+    sha256_hex(key.as_bytes())[..16].to_string()
+}
+
+/// Checks a hex signature by the key with id `signer` over `message`.
+pub fn verify_bytes(key: &VerifyingKey, signer: &str, message: &[u8], signature: &str) -> Result<(), CertificateError> {
+    if signer != key_id(key) {
+        return Err(CertificateError::UnknownKey(signer.to_string()));
+    }
+    let bytes: [u8; 64] = hex::decode(signature).ok()
+        .and_then(|bytes| bytes.try_into().ok())
+        .ok_or(CertificateError::MalformedSignature)?;
+    key.verify(message, &Signature::from_bytes(&bytes))
+        .map_err(|_| CertificateError::InvalidSignature)
+}
+
+/// Checks a certificate against an issuer public key.
+pub fn verify(key: &VerifyingKey, certificate: &SignedCertificate) -> Result<(), CertificateError> {
+    verify_bytes(key, &certificate.key_id, &certificate.payload.canonical_bytes(), &certificate.signature)
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    /// A certificate payload for tests in this and the derived formats.
+    pub(crate) fn payload() -> CertificatePayload {
+        CertificatePayload {
+            version: PAYLOAD_VERSION,
+            certificate_id: "CERT_00112233445566778899aabbccddeeff".to_string(),
+            quiz_type: "math".to_string(),
+            level: 4,
+            score_commitment: score_commitment("enc_math_4_salt"),
+            cheating_likelihood_bps: 1200,
+            user_id: "alice".to_string(),
+            issued_at: 1_700_000_000,
+            expires_at: Some(1_800_000_000),
+            session_hash: session_hash("session-1", &["ct1:a".to_string(), "ct1:b".to_string()]),
+        }
+    }
+
+    #[test]
+    fn signed_certificate_verifies() {
+        let key = IssuerKey::generate();
+        let certificate = key.sign(payload());
+        assert_eq!(certificate.key_id, key.key_id());
+        assert_eq!(verify(&key.verifying_key(), &certificate), Ok(()));
+    }
+
+    #[test]
+    fn tampered_payload_is_rejected() {
+        let key = IssuerKey::generate();
+        let certificate = key.sign(payload());
+
+        let mut tampered = certificate.clone();
+        tampered.payload.level = 5;
+        assert_eq!(verify(&key.verifying_key(), &tampered), Err(CertificateError::InvalidSignature));
+
+        let mut tampered = certificate.clone();
+        tampered.payload.expires_at = None;
+        assert_eq!(verify(&key.verifying_key(), &tampered), Err(CertificateError::InvalidSignature));
+
+        // Escaping keeps a newline in a text field from shifting the others
+        let mut shifted = payload();
+        shifted.user_id = "alice\n1700000000".to_string();
+        assert_ne!(shifted.canonical_bytes(), payload().canonical_bytes());
+    }
+
+    #[test]
+    fn wrong_key_and_bad_signature_are_rejected() {
+        let key = IssuerKey::generate();
+        let other = IssuerKey::generate();
+        let certificate = key.sign(payload());
+        assert_eq!(
+            verify(&other.verifying_key(), &certificate),
+            Err(CertificateError::UnknownKey(key.key_id())),
+        );
+
+        let mut truncated = certificate.clone();
+        truncated.signature.truncate(126);
+        assert_eq!(verify(&key.verifying_key(), &truncated), Err(CertificateError::MalformedSignature));
+
+        let mut forged = certificate;
+        forged.signature = other.sign(payload()).signature;
+        assert_eq!(verify(&key.verifying_key(), &forged), Err(CertificateError::InvalidSignature));
+    }
+
+    #[test]
+    fn session_hash_separates_answers() {
+        let joined = session_hash("s", &["ab".to_string()]);
+        let split = session_hash("s", &["a".to_string(), "b".to_string()]);
+        assert_ne!(joined, split);
+        assert_eq!(joined.len(), 64);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn issuer_key_file_is_private() {
+        use std::os::unix::fs::PermissionsExt;
+        let key = IssuerKey::generate();
+        let path = std::env::temp_dir().join(format!("ppot-issuer-{}.key", key.key_id()));
+        key.save(&path).unwrap();
+
+        assert_eq!(std::fs::metadata(&path).unwrap().permissions().mode() & 0o777, 0o600);
+        assert_eq!(IssuerKey::load(&path).unwrap().key_id(), key.key_id());
+        assert!(key.save(&path).is_err(), "an existing key is never overwritten");
+
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o644)).unwrap();
+        let error = IssuerKey::load(&path).err().unwrap();
+        assert_eq!(error.kind(), std::io::ErrorKind::PermissionDenied);
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,296 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::path::Path;
+use crate::accommodations::Accommodation;
+use crate::dynamic_questions::BehaviorMetrics;
+
+pub const DETECTOR_COUNT: usize = 11;
+
+pub const DETECTOR_NAMES: [&str; DETECTOR_COUNT] = [
+    "fast_answers",
+    "uniform_timing",
+    "excessive_switching",
+    "fast_perfect",
+    "focus_loss",
+    "paste",
+    "copy",
+    "rapid_selection",
+    "profile_mismatch",
+    "answer_pattern",
+    "missing_timings",
+];
+
+/// Which detectors fired for one submission, indexed like `DETECTOR_NAMES`.
+#[derive(Debug, Serialize, Clone, Copy, Default, PartialEq)]
+pub struct DetectorSignals(pub [bool; DETECTOR_COUNT]);
+
+// Unknown names are rejected, so a model always has one weight per detector
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct DetectorWeights {
+    pub fast_answers: f32,
+    pub uniform_timing: f32,
+    pub excessive_switching: f32,
+    pub fast_perfect: f32,
+    pub focus_loss: f32,
+    pub paste: f32,
+    pub copy: f32,
+    pub rapid_selection: f32,
+    pub profile_mismatch: f32,
+    pub answer_pattern: f32,
+    #[serde(default = "default_missing_timings_weight")]
+    pub missing_timings: f32, // absent from models tuned before the detector existed
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct CheatingModel {
+    pub weights: DetectorWeights,
+    pub flag_threshold: f32,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ModelError {
+    WeightOutOfRange { detector: &'static str, weight: f32 },
+    ThresholdOutOfRange(f32),
+}
+
+impl fmt::Display for ModelError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ModelError::WeightOutOfRange { detector, weight } => {
+                write!(f, "weight {} for {} is outside 0..=1", weight, detector)
+            }
+            ModelError::ThresholdOutOfRange(t) => write!(f, "flag threshold {} is outside 0..=1", t),
+        }
+    }
+}
+
+impl DetectorSignals {
+    /// Answer times in `metrics` are as observed; the fast-answer floors are
+    /// relaxed for time accommodations instead.
+    pub fn from_metrics(metrics: &BehaviorMetrics, question_count: usize, accommodation: &Accommodation) -> Self {
+        let mut fired = [false; DETECTOR_COUNT];
+        let times = &metrics.answer_times;
+
+        // Timing detectors judge the times that were reported; missing ones
+        // are detector 11's concern and never switch the others off
+        if !times.is_empty() {
+            let avg_time: f32 = times.iter().map(|&t| u64::from(t)).sum::<u64>() as f32 / times.len() as f32;
+
+            // 1. Extremely fast answers
+            let fast_answers = times.iter().filter(|&&t| (t as f32) < accommodation.time_floor(3.0)).count() as f32;
+            fired[0] = fast_answers > times.len() as f32 * 0.5;
+
+            // 2. Timing too consistent (bot-like)
+            let time_variance: f32 = times.iter()
+                .map(|&t| (t as f32 - avg_time).powi(2))
+                .sum::<f32>() / times.len() as f32;
+            fired[1] = time_variance < 1.0;
+
+            // 4. Every question answered very fast
+            fired[3] = avg_time < accommodation.time_floor(5.0) && times.len() == question_count;
+        }
+
+        // 3. Excessive answer switching
+        fired[2] = !accommodation.ignore_switches
+            && metrics.switch_count as f32 / question_count.max(1) as f32 > 0.8;
+
+        // 5. Leaving the quiz tab or window; screen readers move focus on their own
+        let telemetry = &metrics.telemetry;
+        fired[4] = !accommodation.screen_reader
+            && (telemetry.hidden_ratio > 0.2 || telemetry.hidden_count + telemetry.blur_count >= 3);
+
+        // 6-7. Clipboard use; pasting into a multiple-choice quiz is the stronger signal
+        fired[5] = telemetry.paste_count > 0;
+        fired[6] = telemetry.paste_count == 0 && telemetry.copy_count > 0;
+
+        // 8. Answers selected faster than they could be read
+        fired[7] = telemetry.rapid_selection_ratio > 0.5;
+
+        // 9. Input dynamics, timing or accuracy that don't match the candidate's history
+        fired[8] = metrics.consistency_score < 0.5;
+
+        // 10. Scripted answer positions
+        fired[9] = metrics.answer_pattern.is_some();
+
+        // 11. Fewer answer times than questions, which would hide the pace
+        fired[10] = times.len() < question_count;
+
+        DetectorSignals(fired)
+    }
+
+    pub fn fired_names(&self) -> Vec<&'static str> {
+        DETECTOR_NAMES.iter().zip(self.0).filter(|(_, fired)| *fired).map(|(name, _)| *name).collect()
+    }
+}
+
+impl DetectorWeights {
+    pub fn to_array(&self) -> [f32; DETECTOR_COUNT] {
+        [
+            self.fast_answers,
+            self.uniform_timing,
+            self.excessive_switching,
+            self.fast_perfect,
+            self.focus_loss,
+            self.paste,
+            self.copy,
+            self.rapid_selection,
+            self.profile_mismatch,
+            self.answer_pattern,
+            self.missing_timings,
+        ]
+    }
+
+    pub fn from_array(w: [f32; DETECTOR_COUNT]) -> Self {
+        DetectorWeights {
+            fast_answers: w[0],
+            uniform_timing: w[1],
+            excessive_switching: w[2],
+            fast_perfect: w[3],
+            focus_loss: w[4],
+            paste: w[5],
+            copy: w[6],
+            rapid_selection: w[7],
+            profile_mismatch: w[8],
+            answer_pattern: w[9],
+            missing_timings: w[10],
+        }
+    }
+}
+
+fn default_missing_timings_weight() -> f32 {
+    CheatingModel::default().weights.missing_timings
+}
+
+impl Default for CheatingModel {
+    fn default() -> Self {
+        CheatingModel {
+            weights: DetectorWeights::from_array([0.4, 0.3, 0.2, 0.3, 0.3, 0.3, 0.1, 0.2, 0.4, 0.3, 0.3]),
+            flag_threshold: 0.6, // Flag if cheating likelihood > 60%
+        }
+    }
+}
+
+impl CheatingModel {
+    /// Loads a model and rejects weights or thresholds outside 0..=1.
+    pub fn load(path: &Path) -> std::io::Result<Self> {
+        let contents = std::fs::read_to_string(path)?;
+        let model: CheatingModel = serde_json::from_str(&contents)
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?;
+        model.validate().map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e.to_string()))?;
+        Ok(model)
+    }
+
+    /// Ranges also exclude NaN and infinities.
+    pub fn validate(&self) -> Result<(), ModelError> {
+        for (detector, weight) in DETECTOR_NAMES.iter().zip(self.weights.to_array()) {
+            if !(0.0..=1.0).contains(&weight) {
+                return Err(ModelError::WeightOutOfRange { detector, weight });
+            }
+        }
+        if !(0.0..=1.0).contains(&self.flag_threshold) {
+            return Err(ModelError::ThresholdOutOfRange(self.flag_threshold));
+        }
+        Ok(())
+    }
+
+    pub fn save(&self, path: &Path) -> std::io::Result<()> {
+        let contents = serde_json::to_string_pretty(self)
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?;
+        std::fs::write(path, contents)
+    }
+
+    pub fn likelihood(&self, signals: &DetectorSignals) -> f32 {
+        self.weights.to_array().iter()
+            .zip(signals.0)
+            .filter(|(_, fired)| *fired)
+            .map(|(weight, _)| weight)
+            .sum::<f32>()
+            .min(1.0)
+    }
+
+    pub fn is_flagged(&self, likelihood: f32) -> bool {
+        likelihood > self.flag_threshold
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::answer_patterns::AnswerPattern;
+    use crate::dynamic_questions::UserSession;
+
+    fn signals(answer_times: &[u32], question_count: usize) -> Vec<&'static str> {
+        let mut session = UserSession::new("alice".to_string(), "math", question_count);
+        session.behavior_metrics.answer_times = answer_times.to_vec();
+        session.behavior_metrics.telemetry.paste_count = 1;
+        session.record_answer_pattern(Some(AnswerPattern::SameIndex { index: 0 }));
+        session.detector_signals().fired_names()
+    }
+
+    #[test]
+    fn timing_free_detectors_run_without_times() {
+        assert_eq!(signals(&[], 3), ["paste", "answer_pattern", "missing_timings"]);
+    }
+
+    #[test]
+    fn short_timings_are_a_signal() {
+        assert!(signals(&[30, 45], 3).contains(&"missing_timings"));
+        assert!(!signals(&[30, 45, 38], 3).contains(&"missing_timings"));
+    }
+
+    #[test]
+    fn extended_time_never_tightens_the_fast_answer_floors() {
+        let fired = |answer_times: &[u32], time_multiplier: f32| {
+            let accommodation = Accommodation { time_multiplier, ..Default::default() };
+            let mut session = UserSession::new("alice".to_string(), "math", 3).with_accommodation(accommodation);
+            session.behavior_metrics.answer_times = answer_times.to_vec();
+            session.detector_signals().fired_names()
+        };
+        assert!(fired(&[2, 2, 4], 1.0).contains(&"fast_answers"));
+        assert!(!fired(&[2, 2, 4], 2.0).contains(&"fast_answers"));
+        // Times a standard candidate passes with stay clean under extra time
+        assert!(!fired(&[4, 6, 5], 1.0).contains(&"fast_perfect"));
+        assert!(!fired(&[4, 6, 5], 4.0).contains(&"fast_perfect"));
+    }
+
+    #[test]
+    fn older_models_get_the_default_missing_timings_weight() {
+        let mut json = serde_json::to_value(CheatingModel::default()).unwrap();
+        json["weights"].as_object_mut().unwrap().remove("missing_timings");
+        let model: CheatingModel = serde_json::from_value(json).unwrap();
+        assert_eq!(model, CheatingModel::default());
+    }
+
+    #[test]
+    fn load_rejects_out_of_range_or_unknown_weights() {
+        let path = std::env::temp_dir().join(format!("ppot-model-{}.json", rand::random::<u64>()));
+        let load = |json: serde_json::Value| {
+            std::fs::write(&path, json.to_string()).unwrap();
+            CheatingModel::load(&path).map_err(|e| e.to_string())
+        };
+        let model = serde_json::to_value(CheatingModel::default()).unwrap();
+        assert_eq!(load(model.clone()), Ok(CheatingModel::default()));
+
+        let mut heavy = model.clone();
+        heavy["weights"]["paste"] = 1.5.into();
+        assert_eq!(load(heavy), Err("weight 1.5 for paste is outside 0..=1".to_string()));
+
+        let mut overflowing = model.clone();
+        overflowing["weights"]["copy"] = 1e40.into();
+        assert!(load(overflowing).is_err());
+
+        let mut unreachable = model.clone();
+        unreachable["flag_threshold"] = (-0.1).into();
+        assert_eq!(load(unreachable), Err("flag threshold -0.1 is outside 0..=1".to_string()));
+
+        let mut renamed = model;
+        renamed["weights"]["pasted"] = renamed["weights"]["paste"].take();
+        renamed["weights"].as_object_mut().unwrap().remove("paste");
+        assert!(load(renamed).is_err());
+        std::fs::remove_file(&path).unwrap();
+
+        let nan = CheatingModel { flag_threshold: f32::NAN, ..Default::default() };
+        assert!(matches!(nan.validate(), Err(ModelError::ThresholdOutOfRange(_))));
+    }
+}
@@ -0,0 +1,282 @@
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fmt;
+
+pub const ENVELOPE_VERSION: u8 = 1;
+// Multi-part answers carry one envelope per part
+pub const PART_SEPARATOR: char = '|';
+const MAX_KEY_ID_LEN: usize = 64;
+
+/// How a ciphertext was produced, and so how the backend evaluates it.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum Scheme {
+    /// Keystream cipher under the client's registered key material. Hidden
+    /// from anyone without the key, but not from the server, which holds it.
+    SimXor,
+    /// TFHE ciphertext of a u8 answer index, only evaluable homomorphically.
+    TfheUint8,
+}
+
+impl Scheme {
+    pub fn id(&self) -> &'static str {
+        match self {
+            Scheme::SimXor => "sim-xor",
+            Scheme::TfheUint8 => "tfhe-uint8",
+        }
+    }
+
+    fn from_id(id: &str) -> Option<Self> {
+        [Scheme::SimXor, Scheme::TfheUint8].into_iter().find(|scheme| scheme.id() == id)
+    }
+}
+
+/// `ct<version>:<scheme>:<key id>:<nonce>:<ciphertext>`, with the nonce and
+/// ciphertext in standard base64.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Envelope {
+    pub scheme: Scheme,
+    pub key_id: String,
+    pub nonce: Vec<u8>,
+    pub ciphertext: Vec<u8>,
+}
+
+/// One answer part as submitted.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Ciphertext {
+    Envelope(Envelope),
+    /// Pre-envelope opaque string, e.g. `enc_<answer>_<salt>_<nonce>_<ts>`
+    Legacy(String),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum CiphertextError {
+    UnsupportedVersion(String),
+    UnknownScheme(String),
+    Malformed(String),
+    LegacyRejected { plaintext: bool },
+}
+
+impl fmt::Display for CiphertextError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CiphertextError::UnsupportedVersion(tag) => write!(f, "unsupported envelope version {}", tag),
+            CiphertextError::UnknownScheme(id) => write!(f, "unknown ciphertext scheme {}", id),
+            CiphertextError::Malformed(e) => write!(f, "malformed ciphertext envelope: {}", e),
+            CiphertextError::LegacyRejected { plaintext: true } => {
+                write!(f, "legacy ciphertext carries the answer in plaintext")
+            }
+            CiphertextError::LegacyRejected { plaintext: false } => {
+                write!(f, "legacy ciphertexts are not accepted, send a ct{} envelope", ENVELOPE_VERSION)
+            }
+        }
+    }
+}
+
+impl Envelope {
+    pub fn parse(text: &str) -> Result<Self, CiphertextError> {
+        let fields: Vec<&str> = text.split(':').collect();
+        if fields[0] != format!("ct{}", ENVELOPE_VERSION) {
+            return Err(CiphertextError::UnsupportedVersion(fields[0].to_string()));
+        }
+        let [_, scheme, key_id, nonce, ciphertext] = fields[..] else {
+            return Err(CiphertextError::Malformed(format!("expected 5 fields, got {}", fields.len())));
+        };
+
+        let scheme = Scheme::from_id(scheme).ok_or_else(|| CiphertextError::UnknownScheme(scheme.to_string()))?;
+        let key_id_ok = !key_id.is_empty()
+            && key_id.len() <= MAX_KEY_ID_LEN
+            && key_id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+        if !key_id_ok {
+            return Err(CiphertextError::Malformed(format!("invalid key id {:?}", key_id)));
+        }
+        let decode = |field: &str, name: &str| {
+            STANDARD.decode(field).ok()
+                .filter(|bytes| !bytes.is_empty())
+                .ok_or_else(|| CiphertextError::Malformed(format!("{} is not non-empty base64", name)))
+        };
+
+        Ok(Envelope {
+            scheme,
+            key_id: key_id.to_string(),
+            nonce: decode(nonce, "nonce")?,
+            ciphertext: decode(ciphertext, "ciphertext")?,
+        })
+    }
+
+    pub fn encode(&self) -> String {
+        format!(
+            "ct{}:{}:{}:{}:{}",
+            ENVELOPE_VERSION,
+            self.scheme.id(),
+            self.key_id,
+            STANDARD.encode(&self.nonce),
+            STANDARD.encode(&self.ciphertext),
+        )
+    }
+
+    /// Encrypts an answer index under the simulated scheme, as clients do.
+    /// `key` is the key material registered as `key_id`.
+    pub fn seal_simulated(key_id: &str, key: &[u8], nonce: Vec<u8>, answer: u8) -> Self {
+        let ciphertext = xor_keystream(key, &nonce, &[answer]);
+        Envelope { scheme: Scheme::SimXor, key_id: key_id.to_string(), nonce, ciphertext }
+    }
+
+    /// The answer index, for schemes the backend can decrypt with the key
+    /// material registered under `key_id`.
+    pub fn decrypt(&self, key: &[u8]) -> Option<u8> {
+        match self.scheme {
+            Scheme::SimXor => match xor_keystream(key, &self.nonce, &self.ciphertext)[..] {
+                [answer] => Some(answer),
+                _ => None,
+            },
+            Scheme::TfheUint8 => None,
+        }
+    }
+}
+
+/// SHA-256 in counter mode over the secret key and the nonce.
+fn xor_keystream(key: &[u8], nonce: &[u8], data: &[u8]) -> Vec<u8> {
+    data.chunks(32).enumerate()
+        .flat_map(|(counter, chunk)| {
+            let mut hasher = Sha256::new();
+            hasher.update(b"ppot-sim-xor\n");
+            hasher.update((key.len() as u64).to_be_bytes());
+            hasher.update(key);
+            hasher.update(nonce);
+            hasher.update((counter as u32).to_be_bytes());
+            let block = hasher.finalize();
+            chunk.iter().zip(block).map(|(byte, key)| byte ^ key).collect::<Vec<u8>>()
+        })
+        .collect()
+}
+
+/// Whether a legacy string is `enc_<answer>_...`, with the answer readable.
+fn legacy_has_plaintext(text: &str) -> bool {
+    text.strip_prefix("enc_")
+        .and_then(|rest| rest.split('_').next())
+        .is_some_and(|answer| !answer.is_empty() && answer.chars().all(|c| c.is_ascii_digit()))
+}
+
+impl Ciphertext {
+    pub fn parse(text: &str) -> Result<Self, CiphertextError> {
+        if text.starts_with("ct") && text.contains(':') {
+            Envelope::parse(text).map(Ciphertext::Envelope)
+        } else {
+            Ok(Ciphertext::Legacy(text.to_string()))
+        }
+    }
+}
+
+/// Which submitted answers the backend takes.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct CiphertextPolicy {
+    pub accept_legacy: bool, // for clients that predate the envelope
+}
+
+impl CiphertextPolicy {
+    /// Validates one submitted answer, splitting multi-part answers.
+    /// Empty strings are unanswered items and always pass.
+    pub fn check(&self, answer: &str) -> Result<(), CiphertextError> {
+        if answer.is_empty() {
+            return Ok(());
+        }
+        for part in answer.split(PART_SEPARATOR) {
+            if let Ciphertext::Legacy(text) = Ciphertext::parse(part)? {
+                if !self.accept_legacy {
+                    return Err(CiphertextError::LegacyRejected { plaintext: legacy_has_plaintext(&text) });
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Checks every answer; the error carries the offending answer's index.
+    pub fn check_all(&self, answers: &[String]) -> Result<(), (usize, CiphertextError)> {
+        answers.iter().enumerate()
+            .try_for_each(|(i, answer)| self.check(answer).map_err(|e| (i, e)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn envelope_round_trips() {
+        let envelope = Envelope {
+            scheme: Scheme::TfheUint8,
+            key_id: "0123abcd_key-1".to_string(),
+            nonce: vec![1, 2, 3],
+            ciphertext: vec![0xff; 40],
+        };
+        let text = envelope.encode();
+        assert!(text.starts_with("ct1:tfhe-uint8:0123abcd_key-1:AQID:"));
+        assert_eq!(Envelope::parse(&text), Ok(envelope.clone()));
+        assert_eq!(Ciphertext::parse(&text), Ok(Ciphertext::Envelope(envelope)));
+        assert_eq!(Ciphertext::parse("enc_2_x"), Ok(Ciphertext::Legacy("enc_2_x".to_string())));
+    }
+
+    #[test]
+    fn malformed_envelopes_are_rejected() {
+        assert_eq!(
+            Envelope::parse("ct2:sim-xor:k:AQ==:AQ=="),
+            Err(CiphertextError::UnsupportedVersion("ct2".to_string())),
+        );
+        assert_eq!(
+            Envelope::parse("ct1:rot13:k:AQ==:AQ=="),
+            Err(CiphertextError::UnknownScheme("rot13".to_string())),
+        );
+        let malformed = [
+            "ct1:sim-xor:k:AQ==",
+            "ct1:sim-xor:k:AQ==:AQ==:AQ==",
+            "ct1:sim-xor::AQ==:AQ==",
+            "ct1:sim-xor:k/1:AQ==:AQ==",
+            "ct1:sim-xor:k:not base64:AQ==",
+            "ct1:sim-xor:k:AQ==:",
+        ];
+        for text in malformed {
+            assert!(matches!(Envelope::parse(text), Err(CiphertextError::Malformed(_))), "{}", text);
+        }
+        let long_id = format!("ct1:sim-xor:{}:AQ==:AQ==", "k".repeat(MAX_KEY_ID_LEN + 1));
+        assert!(matches!(Envelope::parse(&long_id), Err(CiphertextError::Malformed(_))));
+    }
+
+    #[test]
+    fn simulated_answers_need_the_registered_key() {
+        let key = [7u8; 32];
+        let envelope = Envelope::seal_simulated("k1", &key, vec![9; 12], 2);
+        let parsed = Envelope::parse(&envelope.encode()).unwrap();
+        assert_eq!(parsed.decrypt(&key), Some(2));
+        assert_ne!(parsed.ciphertext, vec![2]);
+
+        let mut other = key;
+        other[0] ^= 1;
+        assert_ne!(parsed.decrypt(&other), Some(2));
+        // A longer key with the same prefix must give a different keystream
+        assert_ne!(xor_keystream(&key, &[9; 12], &[0]), xor_keystream(&[7u8; 33], &[9; 12], &[0]));
+
+        let mut tfhe = parsed;
+        tfhe.scheme = Scheme::TfheUint8;
+        assert_eq!(tfhe.decrypt(&key), None);
+    }
+
+    #[test]
+    fn policy_gates_legacy_answers() {
+        let strict = CiphertextPolicy::default();
+        let lenient = CiphertextPolicy { accept_legacy: true };
+        let envelope = Envelope::seal_simulated("k1", &[7; 32], vec![1], 0).encode();
+
+        assert_eq!(strict.check(""), Ok(()));
+        assert_eq!(strict.check(&format!("{}|{}", envelope, envelope)), Ok(()));
+        assert_eq!(strict.check("enc_3_salt_nonce_1"), Err(CiphertextError::LegacyRejected { plaintext: true }));
+        assert_eq!(strict.check("opaque-blob"), Err(CiphertextError::LegacyRejected { plaintext: false }));
+        assert_eq!(lenient.check("enc_3_salt_nonce_1"), Ok(()));
+
+        let answers = vec![envelope.clone(), format!("{}|enc_1_x", envelope)];
+        assert_eq!(strict.check_all(&answers), Err((1, CiphertextError::LegacyRejected { plaintext: true })));
+        assert_eq!(lenient.check_all(&answers), Ok(()));
+    }
+}
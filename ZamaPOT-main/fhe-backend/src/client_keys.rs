@@ -0,0 +1,306 @@
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::{Arc, Mutex};
+use crate::certificates;
+use crate::ciphertext::Scheme;
+
+pub const DEFAULT_MAX_KEY_BYTES: usize = 64 * 1024 * 1024;
+pub const DEFAULT_MAX_TOTAL_BYTES: usize = 1024 * 1024 * 1024;
+pub const DEFAULT_MAX_KEYS_PER_USER: usize = 4;
+pub const DEFAULT_KEY_TTL_SECS: u64 = 24 * 60 * 60;
+
+#[derive(Debug, Clone, Copy)]
+pub struct KeyLimits {
+    pub max_key_bytes: usize,     // public and server key together
+    pub max_total_bytes: usize,   // across every live key
+    pub max_keys_per_user: usize,
+    pub ttl_secs: u64,
+}
+
+impl Default for KeyLimits {
+    fn default() -> Self {
+        KeyLimits {
+            max_key_bytes: DEFAULT_MAX_KEY_BYTES,
+            max_total_bytes: DEFAULT_MAX_TOTAL_BYTES,
+            max_keys_per_user: DEFAULT_MAX_KEYS_PER_USER,
+            ttl_secs: DEFAULT_KEY_TTL_SECS,
+        }
+    }
+}
+
+/// A client's key material, base64 encoded. `server_key` is the evaluation
+/// key the backend computes under; the client key never leaves the device.
+#[derive(Debug, Deserialize)]
+pub struct KeyRegistration {
+    pub user_id: String,
+    #[serde(default)]
+    pub session_id: Option<String>, // bind this session to the key right away
+    pub scheme: Scheme,
+    #[serde(default)]
+    pub public_key: Option<String>,
+    pub server_key: String,
+}
+
+#[derive(Debug)]
+pub struct ClientKey {
+    pub key_id: String,
+    pub user_id: String,
+    pub scheme: Scheme,
+    pub public_key: Option<Vec<u8>>,
+    pub server_key: Vec<u8>,
+    pub registered_at: u64, // unix seconds
+    pub expires_at: u64,    // unix seconds
+    token_hash: String,     // sha256 hex of the token that manages the key
+}
+
+/// What the API reports about a key; never the key material or its owner.
+#[derive(Debug, Serialize, Clone)]
+pub struct KeyInfo {
+    pub key_id: String,
+    pub scheme: Scheme,
+    pub public_key_bytes: usize,
+    pub server_key_bytes: usize,
+    pub registered_at: u64,
+    pub expires_at: u64,
+}
+
+/// Registration result. The token is shown once and is needed to read or
+/// remove the key.
+#[derive(Debug, Serialize)]
+pub struct RegisteredKey {
+    #[serde(flatten)]
+    pub info: KeyInfo,
+    pub key_token: String,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum KeyError {
+    Malformed(String),
+    TooLarge { bytes: usize, limit: usize },
+    TooManyKeys { limit: usize },
+    StoreFull,
+    UnknownKey(String),
+    Expired(String),
+    WrongOwner(String),
+    InvalidToken(String),
+    UnknownSession(String),
+    UnboundKey { session: String, ciphertext: String },
+    SchemeMismatch { key: Scheme, ciphertext: Scheme },
+}
+
+impl fmt::Display for KeyError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            KeyError::Malformed(e) => write!(f, "malformed key: {}", e),
+            KeyError::TooLarge { bytes, limit } => write!(f, "key is {} bytes, the limit is {}", bytes, limit),
+            KeyError::TooManyKeys { limit } => write!(f, "user already has {} live keys", limit),
+            KeyError::StoreFull => write!(f, "key store is full, try again later"),
+            KeyError::UnknownKey(id) => write!(f, "key {} is not registered", id),
+            KeyError::Expired(id) => write!(f, "key {} has expired", id),
+            KeyError::WrongOwner(id) => write!(f, "key {} belongs to another user", id),
+            KeyError::InvalidToken(id) => write!(f, "invalid token for key {}", id),
+            KeyError::UnknownSession(id) => write!(f, "session {} not found", id),
+            KeyError::UnboundKey { session, ciphertext } => {
+                write!(f, "answer is encrypted under key {} but the session is bound to {}", ciphertext, session)
+            }
+            KeyError::SchemeMismatch { key, ciphertext } => {
+                write!(f, "ciphertext uses {} but the key is for {}", ciphertext.id(), key.id())
+            }
+        }
+    }
+}
+
+impl ClientKey {
+    fn size(&self) -> usize {
+        self.server_key.len() + self.public_key.as_ref().map_or(0, Vec::len)
+    }
+
+    pub fn info(&self) -> KeyInfo {
+        KeyInfo {
+            key_id: self.key_id.clone(),
+            scheme: self.scheme,
+            public_key_bytes: self.public_key.as_ref().map_or(0, Vec::len),
+            server_key_bytes: self.server_key.len(),
+            registered_at: self.registered_at,
+            expires_at: self.expires_at,
+        }
+    }
+}
+
+/// Key id: the first 8 bytes of the SHA-256 over the scheme and key material,
+/// so registering the same key again yields the same id.
+fn key_id(scheme: Scheme, public_key: Option<&[u8]>, server_key: &[u8]) -> String {
+    let mut material = format!("ppot-client-key\n{}\n", scheme.id()).into_bytes();
+    material.extend_from_slice(&(public_key.map_or(0, <[u8]>::len) as u64).to_be_bytes());
+    material.extend_from_slice(public_key.unwrap_or_default());
+    material.extend_from_slice(server_key);
+    certificates::sha256_hex(&material)[..16].to_string()
+}
+
+/// Registered client keys by id. Keys expire `ttl_secs` after their last
+/// registration and are dropped lazily.
+#[derive(Debug, Default)]
+pub struct KeyStore {
+    keys: Mutex<HashMap<String, Arc<ClientKey>>>,
+    limits: KeyLimits,
+}
+
+impl KeyStore {
+    pub fn new(limits: KeyLimits) -> Self {
+        KeyStore { keys: Mutex::new(HashMap::new()), limits }
+    }
+
+    /// Stores a key, or extends the lifetime of an identical one. Either way
+    /// a fresh management token is issued.
+    pub fn register(&self, registration: &KeyRegistration, now: u64) -> Result<RegisteredKey, KeyError> {
+        let decode = |field: &str, name: &str| {
+            STANDARD.decode(field).ok()
+                .filter(|bytes| !bytes.is_empty())
+                .ok_or_else(|| KeyError::Malformed(format!("{} is not non-empty base64", name)))
+        };
+        let server_key = decode(&registration.server_key, "server_key")?;
+        let public_key = registration.public_key.as_deref()
+            .map(|public_key| decode(public_key, "public_key"))
+            .transpose()?;
+        let bytes = server_key.len() + public_key.as_ref().map_or(0, Vec::len);
+        if bytes > self.limits.max_key_bytes {
+            return Err(KeyError::TooLarge { bytes, limit: self.limits.max_key_bytes });
+        }
+
+        let key_id = key_id(registration.scheme, public_key.as_deref(), &server_key);
+        let mut keys = self.keys.lock().unwrap();
+        keys.retain(|_, key| key.expires_at > now);
+
+        if let Some(existing) = keys.get(&key_id) {
+            if existing.user_id != registration.user_id {
+                return Err(KeyError::WrongOwner(key_id));
+            }
+        } else {
+            let owned = keys.values().filter(|key| key.user_id == registration.user_id).count();
+            if owned >= self.limits.max_keys_per_user {
+                return Err(KeyError::TooManyKeys { limit: self.limits.max_keys_per_user });
+            }
+            let stored: usize = keys.values().map(|key| key.size()).sum();
+            if stored + bytes > self.limits.max_total_bytes {
+                return Err(KeyError::StoreFull);
+            }
+        }
+
+        let mut token = [0u8; 32];
+        rand::rngs::OsRng.fill_bytes(&mut token);
+        let key_token = hex::encode(token);
+        let key = ClientKey {
+            key_id: key_id.clone(),
+            user_id: registration.user_id.clone(),
+            scheme: registration.scheme,
+            public_key,
+            server_key,
+            registered_at: now,
+            expires_at: now + self.limits.ttl_secs,
+            token_hash: certificates::sha256_hex(key_token.as_bytes()),
+        };
+        let info = key.info();
+        keys.insert(key_id, Arc::new(key));
+        Ok(RegisteredKey { info, key_token })
+    }
+
+    /// A live key by id.
+    pub fn get(&self, key_id: &str, now: u64) -> Result<Arc<ClientKey>, KeyError> {
+        let key = self.keys.lock().unwrap().get(key_id).cloned()
+            .ok_or_else(|| KeyError::UnknownKey(key_id.to_string()))?;
+        if key.expires_at <= now {
+            return Err(KeyError::Expired(key_id.to_string()));
+        }
+        Ok(key)
+    }
+
+    /// A live key that `user_id` registered.
+    pub fn owned_by(&self, key_id: &str, user_id: &str, now: u64) -> Result<Arc<ClientKey>, KeyError> {
+        let key = self.get(key_id, now)?;
+        if key.user_id != user_id {
+            return Err(KeyError::WrongOwner(key_id.to_string()));
+        }
+        Ok(key)
+    }
+
+    /// A live key, if `token` is the one issued at its last registration.
+    pub fn authorized(&self, key_id: &str, token: &str, now: u64) -> Result<Arc<ClientKey>, KeyError> {
+        let key = self.get(key_id, now)?;
+        // Comparing digests keeps the comparison time independent of the token
+        if certificates::sha256_hex(token.as_bytes()) != key.token_hash {
+            return Err(KeyError::InvalidToken(key_id.to_string()));
+        }
+        Ok(key)
+    }
+
+    pub fn remove(&self, key_id: &str) -> bool {
+        self.keys.lock().unwrap().remove(key_id).is_some()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn registration(user_id: &str, server_key: &[u8]) -> KeyRegistration {
+        KeyRegistration {
+            user_id: user_id.to_string(),
+            session_id: None,
+            scheme: Scheme::SimXor,
+            public_key: None,
+            server_key: STANDARD.encode(server_key),
+        }
+    }
+
+    fn store(max_key_bytes: usize, max_keys_per_user: usize) -> KeyStore {
+        KeyStore::new(KeyLimits { max_key_bytes, max_total_bytes: 64, max_keys_per_user, ttl_secs: 100 })
+    }
+
+    #[test]
+    fn registration_is_idempotent_and_token_gated() {
+        let keys = store(32, 2);
+        let first = keys.register(&registration("alice", b"key-1"), 1_000).unwrap();
+        let again = keys.register(&registration("alice", b"key-1"), 1_050).unwrap();
+        let key_id = &first.info.key_id;
+        assert_eq!(again.info.key_id, *key_id);
+        assert_eq!(again.info.expires_at, 1_150);
+
+        // Only the latest token manages the key
+        assert!(keys.authorized(key_id, &again.key_token, 1_100).is_ok());
+        assert_eq!(keys.authorized(key_id, &first.key_token, 1_100).err(), Some(KeyError::InvalidToken(key_id.clone())));
+        assert_eq!(
+            keys.register(&registration("bob", b"key-1"), 1_100).err(),
+            Some(KeyError::WrongOwner(key_id.clone())),
+        );
+        assert_eq!(keys.owned_by(key_id, "bob", 1_100).err(), Some(KeyError::WrongOwner(key_id.clone())));
+        assert!(keys.remove(key_id));
+        assert_eq!(keys.get(key_id, 1_100).err(), Some(KeyError::UnknownKey(key_id.clone())));
+    }
+
+    #[test]
+    fn limits_come_from_the_store() {
+        let keys = store(8, 1);
+        assert_eq!(
+            keys.register(&registration("alice", b"too-long-key"), 1_000).err(),
+            Some(KeyError::TooLarge { bytes: 12, limit: 8 }),
+        );
+        assert!(matches!(
+            keys.register(&KeyRegistration { server_key: "not base64!".to_string(), ..registration("alice", b"") }, 1_000),
+            Err(KeyError::Malformed(_)),
+        ));
+
+        let key_id = keys.register(&registration("alice", b"key-1"), 1_000).unwrap().info.key_id;
+        assert_eq!(
+            keys.register(&registration("alice", b"key-2"), 1_000).err(),
+            Some(KeyError::TooManyKeys { limit: 1 }),
+        );
+
+        // Expired keys stop working and free their slot
+        assert_eq!(keys.get(&key_id, 1_100).err(), Some(KeyError::Expired(key_id.clone())));
+        assert!(keys.register(&registration("alice", b"key-2"), 1_100).is_ok());
+    }
+}
@@ -0,0 +1,168 @@
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use crate::dynamic_questions::DynamicQuestion;
+
+// Attempts kept per user and category
+const MAX_HISTORY: usize = 20;
+// Comparisons need at least this many earlier attempts
+const MIN_HISTORY: usize = 2;
+// Difficulty band edges: easy < 0.5 <= medium < 0.8 <= hard
+const BAND_EDGES: [f32; 2] = [0.5, 0.8];
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct AttemptRecord {
+    pub time_ratio: f32,               // mean answer time / mean expected time
+    pub time_consistency: f32,
+    pub pattern_deviation: f32,
+    pub accuracy_by_band: [Option<f32>; 3],
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct AttemptHistory {
+    pub attempts: VecDeque<AttemptRecord>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct ConsistencyReport {
+    pub attempts_compared: usize,
+    pub timing_deviation: f32,   // mean absolute z-score of timing features
+    pub accuracy_deviation: f32, // mean absolute accuracy change per difficulty band
+    pub score: f32,
+}
+
+impl AttemptRecord {
+    /// None when the attempt has no usable timing (no answer times, all of
+    /// them zero, or non-finite features), so it never enters a history.
+    pub fn new(
+        questions: &[DynamicQuestion],
+        answer_times: &[u32],
+        answers_correct: &[bool],
+        time_consistency: f32,
+        pattern_deviation: f32,
+    ) -> Option<Self> {
+        if answer_times.iter().all(|&t| t == 0) || !time_consistency.is_finite() || !pattern_deviation.is_finite() {
+            return None;
+        }
+        let expected: f32 = questions.iter().map(|q| q.expected_time as f32).sum::<f32>()
+            / questions.len().max(1) as f32;
+        let actual: f32 = answer_times.iter().map(|&t| u64::from(t)).sum::<u64>() as f32 / answer_times.len() as f32;
+
+        let mut band_totals = [(0u32, 0u32); 3];
+        for (question, &correct) in questions.iter().zip(answers_correct) {
+            let band = BAND_EDGES.iter().filter(|&&edge| question.difficulty >= edge).count();
+            band_totals[band].0 += correct as u32;
+            band_totals[band].1 += 1;
+        }
+        let accuracy_by_band = band_totals.map(|(correct, total)| {
+            (total > 0).then(|| correct as f32 / total as f32)
+        });
+
+        Some(AttemptRecord {
+            time_ratio: if expected > 0.0 { actual / expected } else { 0.0 },
+            time_consistency,
+            pattern_deviation,
+            accuracy_by_band,
+        })
+    }
+}
+
+impl AttemptHistory {
+    pub fn push(&mut self, record: AttemptRecord) {
+        if self.attempts.len() == MAX_HISTORY {
+            self.attempts.pop_front();
+        }
+        self.attempts.push_back(record);
+    }
+
+    /// Scores how well `current` matches earlier attempts in the same category.
+    /// Returns None until there is enough history to compare against.
+    pub fn compare(&self, current: &AttemptRecord) -> Option<ConsistencyReport> {
+        if self.attempts.len() < MIN_HISTORY {
+            return None;
+        }
+
+        let timing_features: [fn(&AttemptRecord) -> f32; 3] = [
+            |r| r.time_ratio,
+            |r| r.time_consistency,
+            |r| r.pattern_deviation,
+        ];
+        let timing_deviation = timing_features.iter()
+            .map(|feature| {
+                let values: Vec<f32> = self.attempts.iter().map(feature).collect();
+                let mean = values.iter().sum::<f32>() / values.len() as f32;
+                let variance = values.iter().map(|v| (v - mean).powi(2)).sum::<f32>()
+                    / (values.len() - 1) as f32;
+                let floor = (mean.abs() * 0.1).max(0.05);
+                (feature(current) - mean).abs() / variance.sqrt().max(floor)
+            })
+            .sum::<f32>() / timing_features.len() as f32;
+
+        let mut band_diffs = Vec::new();
+        for band in 0..3 {
+            let Some(current_accuracy) = current.accuracy_by_band[band] else { continue };
+            let past: Vec<f32> = self.attempts.iter().filter_map(|r| r.accuracy_by_band[band]).collect();
+            if past.is_empty() {
+                continue;
+            }
+            let past_mean = past.iter().sum::<f32>() / past.len() as f32;
+            band_diffs.push((current_accuracy - past_mean).abs());
+        }
+        let accuracy_deviation = if band_diffs.is_empty() {
+            0.0
+        } else {
+            band_diffs.iter().sum::<f32>() / band_diffs.len() as f32
+        };
+
+        let timing_score = 1.0 / (1.0 + timing_deviation / 3.0);
+        let accuracy_score = 1.0 - accuracy_deviation;
+
+        Some(ConsistencyReport {
+            attempts_compared: self.attempts.len(),
+            timing_deviation,
+            accuracy_deviation,
+            score: timing_score.min(accuracy_score).clamp(0.0, 1.0),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn questions() -> Vec<DynamicQuestion> {
+        [0.3, 0.6, 0.9].iter().map(|&d| DynamicQuestion::generate_math_question("alice", d)).collect()
+    }
+
+    fn attempt(answer_times: &[u32], time_consistency: f32, pattern_deviation: f32) -> Option<AttemptRecord> {
+        AttemptRecord::new(&questions(), answer_times, &[true, true, false], time_consistency, pattern_deviation)
+    }
+
+    #[test]
+    fn attempts_without_usable_timing_are_not_recorded() {
+        assert!(attempt(&[], 1.0, 0.0).is_none());
+        assert!(attempt(&[0, 0, 0], 1.0, 0.0).is_none());
+        assert!(attempt(&[20, 25, 30], f32::NAN, 0.4).is_none());
+        assert!(attempt(&[20, 25, 30], 0.1, f32::INFINITY).is_none());
+
+        let record = attempt(&[20, 25, 30], 0.1, 0.4).unwrap();
+        assert!(record.time_ratio.is_finite() && record.time_ratio > 0.0);
+        assert_eq!(record.accuracy_by_band, [Some(1.0), Some(1.0), Some(0.0)]);
+    }
+
+    #[test]
+    fn comparison_needs_history_and_stays_finite() {
+        let mut history = AttemptHistory::default();
+        let current = attempt(&[20, 25, 30], 0.1, 0.4).unwrap();
+        history.push(current.clone());
+        assert!(history.compare(&current).is_none());
+
+        history.push(attempt(&[22, 24, 31], 0.12, 0.38).unwrap());
+        let similar = history.compare(&current).unwrap();
+        assert_eq!(similar.attempts_compared, 2);
+        assert!(similar.score > 0.5, "{:?}", similar);
+
+        let rushed = AttemptRecord::new(&questions(), &[2, 2, 2], &[false, false, true], 1.0, 0.0).unwrap();
+        let different = history.compare(&rushed).unwrap();
+        assert!(different.score.is_finite() && different.score < similar.score, "{:?}", different);
+    }
+}
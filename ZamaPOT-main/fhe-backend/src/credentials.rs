@@ -0,0 +1,463 @@
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use ed25519_dalek::{Signature, Verifier, VerifyingKey};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use crate::certificates::{IssuerKey, SignedCertificate};
+use crate::registry::{self, SignedRevocationList};
+use crate::scoring;
+
+pub const CREDENTIALS_CONTEXT: &str = "https://www.w3.org/ns/credentials/v2";
+pub const CREDENTIAL_TYPE: &str = "SkillCertificateCredential";
+pub const JWT_TYPE: &str = "vc+jwt";
+pub const REVOCATION_STATUS_TYPE: &str = "PPoTRevocationListEntry";
+// Multicodec prefix for an Ed25519 public key, as used by did:key
+const ED25519_MULTICODEC: [u8; 2] = [0xed, 0x01];
+
+/// A W3C Verifiable Credential (data model 2.0) for one certificate. The
+/// score is never included.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct SkillCredential {
+    #[serde(rename = "@context")]
+    pub context: Vec<String>,
+    pub id: String,
+    #[serde(rename = "type")]
+    pub types: Vec<String>,
+    pub issuer: String,
+    #[serde(rename = "validFrom")]
+    pub valid_from: String,
+    #[serde(rename = "validUntil", default, skip_serializing_if = "Option::is_none")]
+    pub valid_until: Option<String>,
+    #[serde(rename = "credentialSubject")]
+    pub credential_subject: SkillSubject,
+    #[serde(rename = "credentialStatus")]
+    pub credential_status: CredentialStatus,
+}
+
+/// Where a verifier finds out whether the credential has been revoked: the
+/// issuer's signed revocation list, keyed by certificate id.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct CredentialStatus {
+    pub id: String,
+    #[serde(rename = "type")]
+    pub status_type: String,
+    #[serde(rename = "revocationList")]
+    pub revocation_list: String, // URL of the signed revocation list
+    #[serde(rename = "certificateId")]
+    pub certificate_id: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct SkillSubject {
+    pub id: String,
+    #[serde(rename = "skillType")]
+    pub skill_type: String,
+    pub level: u8,
+    #[serde(rename = "levelName")]
+    pub level_name: String,
+    #[serde(rename = "issueDate")]
+    pub issue_date: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct JwtHeader {
+    alg: String,
+    typ: String,
+    kid: String,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum CredentialError {
+    Malformed(String),
+    UnsupportedAlgorithm(String),
+    UnresolvableIssuer(String),
+    IssuerMismatch,
+    InvalidSignature,
+    NotYetValid,
+    Expired,
+    UnsupportedStatus(String),
+    InvalidRevocationList,
+    Revoked,
+}
+
+impl fmt::Display for CredentialError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CredentialError::Malformed(e) => write!(f, "malformed credential: {}", e),
+            CredentialError::UnsupportedAlgorithm(alg) => write!(f, "unsupported algorithm {}", alg),
+            CredentialError::UnresolvableIssuer(did) => write!(f, "cannot resolve issuer {}", did),
+            CredentialError::IssuerMismatch => write!(f, "signing key does not belong to the issuer"),
+            CredentialError::InvalidSignature => write!(f, "signature does not match the credential"),
+            CredentialError::NotYetValid => write!(f, "credential is not valid yet"),
+            CredentialError::Expired => write!(f, "credential has expired"),
+            CredentialError::UnsupportedStatus(kind) => write!(f, "unsupported credential status {}", kind),
+            CredentialError::InvalidRevocationList => write!(f, "revocation list is not signed by the issuer"),
+            CredentialError::Revoked => write!(f, "credential has been revoked"),
+        }
+    }
+}
+
+/// `did:key` identifier for an Ed25519 public key.
+pub fn issuer_did(key: &VerifyingKey) -> String {
+    let mut bytes = ED25519_MULTICODEC.to_vec();
+    bytes.extend_from_slice(key.as_bytes());
+    format!("did:key:z{}", bs58::encode(bytes).into_string())
+}
+
+/// Resolves a `did:key` (with or without a fragment) to its Ed25519 key.
+pub fn resolve_did_key(did: &str) -> Option<VerifyingKey> {
+    let encoded = did.split('#').next()?.strip_prefix("did:key:z")?;
+    let bytes = bs58::decode(encoded).into_vec().ok()?;
+    let key: [u8; 32] = bytes.strip_prefix(&ED25519_MULTICODEC)?.try_into().ok()?;
+    VerifyingKey::from_bytes(&key).ok()
+}
+
+/// Days since the epoch to a proleptic Gregorian (year, month, day).
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+fn days_from_civil(year: i64, month: u32, day: u32) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let yoe = year.rem_euclid(400);
+    let mp = (month as i64 + 9) % 12;
+    let doy = (153 * mp + 2) / 5 + day as i64 - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146_097 + doe - 719_468
+}
+
+/// Unix seconds as an XML Schema dateTime in UTC, e.g. `2025-01-31T12:00:00Z`.
+pub fn format_timestamp(secs: u64) -> String {
+    let (year, month, day) = civil_from_days((secs / 86_400) as i64);
+    let time = secs % 86_400;
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+        year, month, day, time / 3_600, time % 3_600 / 60, time % 60,
+    )
+}
+
+fn digits(field: &[u8]) -> Option<i64> {
+    field.iter().all(u8::is_ascii_digit)
+        .then(|| field.iter().fold(0, |n, d| n * 10 + i64::from(d - b'0')))
+}
+
+/// Parses an RFC 3339 date-time, e.g. `2025-01-31T12:00:00Z` or
+/// `2025-01-31T13:00:00.5+01:00`, to unix seconds. Fractional seconds are
+/// dropped; anything outside the grammar or the calendar is None.
+pub fn parse_timestamp(text: &str) -> Option<u64> {
+    let bytes = text.as_bytes();
+    if bytes.len() < 20
+        || bytes[4] != b'-' || bytes[7] != b'-'
+        || !matches!(bytes[10], b'T' | b't')
+        || bytes[13] != b':' || bytes[16] != b':'
+    {
+        return None;
+    }
+    let (year, month, day) = (digits(&bytes[0..4])?, digits(&bytes[5..7])?, digits(&bytes[8..10])?);
+    let (hour, minute, second) = (digits(&bytes[11..13])?, digits(&bytes[14..16])?, digits(&bytes[17..19])?);
+    // 60 is a leap second; unix time folds it into the next minute
+    if !(1..=12).contains(&month) || hour > 23 || minute > 59 || second > 60 {
+        return None;
+    }
+    let days = days_from_civil(year, month as u32, u32::try_from(day).ok()?);
+    if civil_from_days(days) != (year, month as u32, day as u32) {
+        return None; // e.g. February 30th
+    }
+
+    let mut rest = &bytes[19..];
+    if let Some(fraction) = rest.strip_prefix(b".") {
+        let len = fraction.iter().take_while(|d| d.is_ascii_digit()).count();
+        if len == 0 {
+            return None;
+        }
+        rest = &fraction[len..];
+    }
+    let offset = match rest {
+        [b'Z' | b'z'] => 0,
+        [sign @ (b'+' | b'-'), h1, h2, b':', m1, m2] => {
+            let (hours, minutes) = (digits(&[*h1, *h2])?, digits(&[*m1, *m2])?);
+            if hours > 23 || minutes > 59 {
+                return None;
+            }
+            let offset = hours * 3_600 + minutes * 60;
+            if *sign == b'-' { -offset } else { offset }
+        }
+        _ => return None,
+    };
+
+    u64::try_from(days * 86_400 + hour * 3_600 + minute * 60 + second - offset).ok()
+}
+
+/// Status entry pointing at the revocation list served under `base_url`.
+pub fn revocation_status(certificate_id: &str, base_url: &str) -> CredentialStatus {
+    let revocation_list = format!("{}/certificates/revocations", base_url);
+    CredentialStatus {
+        id: format!("{}#{}", revocation_list, certificate_id),
+        status_type: REVOCATION_STATUS_TYPE.to_string(),
+        revocation_list,
+        certificate_id: certificate_id.to_string(),
+    }
+}
+
+impl SkillCredential {
+    pub fn from_certificate(certificate: &SignedCertificate, issuer: String, base_url: &str) -> Self {
+        let payload = &certificate.payload;
+        let issue_date = format_timestamp(payload.issued_at);
+        SkillCredential {
+            context: vec![CREDENTIALS_CONTEXT.to_string()],
+            id: format!("urn:ppot:certificate:{}", payload.certificate_id),
+            types: vec!["VerifiableCredential".to_string(), CREDENTIAL_TYPE.to_string()],
+            issuer,
+            valid_from: issue_date.clone(),
+            valid_until: payload.expires_at.map(format_timestamp),
+            credential_subject: SkillSubject {
+                id: format!("urn:ppot:user:{}", payload.user_id),
+                skill_type: payload.quiz_type.clone(),
+                level: payload.level,
+                level_name: scoring::level_name(payload.level).to_string(),
+                issue_date,
+            },
+            credential_status: revocation_status(&payload.certificate_id, base_url),
+        }
+    }
+}
+
+fn encode_segment<T: Serialize>(value: &T) -> String {
+    URL_SAFE_NO_PAD.encode(serde_json::to_vec(value).unwrap_or_default())
+}
+
+fn decode_segment<T: DeserializeOwned>(segment: &str) -> Result<T, CredentialError> {
+    let bytes = URL_SAFE_NO_PAD.decode(segment).map_err(|e| CredentialError::Malformed(e.to_string()))?;
+    serde_json::from_slice(&bytes).map_err(|e| CredentialError::Malformed(e.to_string()))
+}
+
+/// Signs any credential document as a JWT (VC-JOSE-COSE): the payload is
+/// the document itself, signed with EdDSA under the issuer's did:key.
+pub fn sign_jwt<T: Serialize>(key: &IssuerKey, document: &T) -> String {
+    let did = issuer_did(&key.verifying_key());
+    let header = JwtHeader {
+        alg: "EdDSA".to_string(),
+        typ: JWT_TYPE.to_string(),
+        kid: format!("{}#{}", did, did.trim_start_matches("did:key:")),
+    };
+    let signing_input = format!("{}.{}", encode_segment(&header), encode_segment(document));
+    let signature = key.sign_raw(signing_input.as_bytes());
+    format!("{}.{}", signing_input, URL_SAFE_NO_PAD.encode(signature))
+}
+
+/// Checks a JWT's signature against the did:key in its header. Returns the
+/// signer's DID and the document; callers match the DID to the issuer.
+pub fn decode_jwt<T: DeserializeOwned>(jwt: &str) -> Result<(String, T), CredentialError> {
+    let mut parts = jwt.split('.');
+    let (Some(header), Some(payload), Some(signature), None) = (parts.next(), parts.next(), parts.next(), parts.next()) else {
+        return Err(CredentialError::Malformed("expected three JWT segments".to_string()));
+    };
+
+    let header: JwtHeader = decode_segment(header)?;
+    if header.alg != "EdDSA" {
+        return Err(CredentialError::UnsupportedAlgorithm(header.alg));
+    }
+    let signer = header.kid.split('#').next().unwrap_or_default().to_string();
+    let key = resolve_did_key(&signer).ok_or_else(|| CredentialError::UnresolvableIssuer(signer.clone()))?;
+
+    let signing_input = &jwt[..jwt.len() - signature.len() - 1];
+    let signature: [u8; 64] = URL_SAFE_NO_PAD.decode(signature).ok()
+        .and_then(|bytes| bytes.try_into().ok())
+        .ok_or_else(|| CredentialError::Malformed("signature is not 64 bytes".to_string()))?;
+    key.verify(signing_input.as_bytes(), &Signature::from_bytes(&signature))
+        .map_err(|_| CredentialError::InvalidSignature)?;
+
+    Ok((signer, decode_segment(payload)?))
+}
+
+/// Checks `validFrom` / `validUntil` at `now`.
+pub fn check_validity(valid_from: &str, valid_until: Option<&str>, now: u64) -> Result<(), CredentialError> {
+    let valid_from = parse_timestamp(valid_from)
+        .ok_or_else(|| CredentialError::Malformed("invalid validFrom".to_string()))?;
+    if now < valid_from {
+        return Err(CredentialError::NotYetValid);
+    }
+    if let Some(valid_until) = valid_until {
+        let valid_until = parse_timestamp(valid_until)
+            .ok_or_else(|| CredentialError::Malformed("invalid validUntil".to_string()))?;
+        if now >= valid_until {
+            return Err(CredentialError::Expired);
+        }
+    }
+    Ok(())
+}
+
+/// Checks a status entry against the issuer's revocation list, which must
+/// be signed by the same key as the credential.
+pub fn check_status(status: &CredentialStatus, issuer: &str, revocations: &SignedRevocationList) -> Result<(), CredentialError> {
+    if status.status_type != REVOCATION_STATUS_TYPE {
+        return Err(CredentialError::UnsupportedStatus(status.status_type.clone()));
+    }
+    let key = resolve_did_key(issuer).ok_or_else(|| CredentialError::UnresolvableIssuer(issuer.to_string()))?;
+    registry::verify_revocation_list(&key, revocations).map_err(|_| CredentialError::InvalidRevocationList)?;
+    if revocations.list.is_revoked(&status.certificate_id) {
+        return Err(CredentialError::Revoked);
+    }
+    Ok(())
+}
+
+pub fn issue_jwt(key: &IssuerKey, certificate: &SignedCertificate, base_url: &str) -> String {
+    let credential = SkillCredential::from_certificate(certificate, issuer_did(&key.verifying_key()), base_url);
+    sign_jwt(key, &credential)
+}
+
+/// Verifies a skill credential issued by any `did:key` issuer, its validity
+/// window at `now`, and its status against `revocations`, a list fetched
+/// from the status entry's `revocationList`. Callers decide whether they
+/// trust the issuer.
+pub fn verify_jwt(jwt: &str, now: u64, revocations: &SignedRevocationList) -> Result<SkillCredential, CredentialError> {
+    let (signer, credential): (String, SkillCredential) = decode_jwt(jwt)?;
+    if signer != credential.issuer {
+        return Err(CredentialError::IssuerMismatch);
+    }
+    check_validity(&credential.valid_from, credential.valid_until.as_deref(), now)?;
+    check_status(&credential.credential_status, &signer, revocations)?;
+    Ok(credential)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::certificates::tests::payload;
+    use crate::registry::{RevocationList, RevokedCertificate, REVOCATION_LIST_VERSION};
+
+    const BASE_URL: &str = "https://ppot.example";
+
+    fn revocations(key: &IssuerKey, revoked: &[&str]) -> SignedRevocationList {
+        RevocationList {
+            version: REVOCATION_LIST_VERSION,
+            issued_at: 1_750_000_000,
+            revocations: revoked.iter()
+                .map(|id| RevokedCertificate { certificate_id: id.to_string(), revoked_at: 1_740_000_000, reason: "test".to_string() })
+                .collect(),
+        }.sign(key)
+    }
+
+    fn tamper_payload(jwt: &str, edit: impl FnOnce(&mut serde_json::Value)) -> String {
+        let parts: Vec<&str> = jwt.split('.').collect();
+        let mut document: serde_json::Value = decode_segment(parts[1]).unwrap();
+        edit(&mut document);
+        format!("{}.{}.{}", parts[0], encode_segment(&document), parts[2])
+    }
+
+    #[test]
+    fn timestamps_round_trip() {
+        assert_eq!(format_timestamp(0), "1970-01-01T00:00:00Z");
+        assert_eq!(format_timestamp(1_700_000_000), "2023-11-14T22:13:20Z");
+        assert_eq!(parse_timestamp("2023-11-14T22:13:20Z"), Some(1_700_000_000));
+        assert_eq!(parse_timestamp("2023-11-14 22:13:20"), None);
+    }
+
+    #[test]
+    fn timestamps_follow_rfc_3339() {
+        assert_eq!(parse_timestamp("2023-11-14T23:13:20.250+01:00"), Some(1_700_000_000));
+        assert_eq!(parse_timestamp("2023-11-14t21:43:20-00:30"), Some(1_700_000_000));
+        assert_eq!(parse_timestamp("2024-02-29T00:00:00Z"), Some(1_709_164_800));
+        for invalid in [
+            "2023-02-29T00:00:00Z",
+            "2023-13-01T00:00:00Z",
+            "2023-11-14T24:00:00Z",
+            "2023-11-14T22:13:20",
+            "2023-11-14T22:13:20.Z",
+            "2023-11-14T22:13:20+0100",
+            "2023-1-14T22:13:20Z",
+            "+023-11-14T22:13:20Z",
+            "1969-12-31T23:59:59Z",
+        ] {
+            assert_eq!(parse_timestamp(invalid), None, "{}", invalid);
+        }
+    }
+
+    #[test]
+    fn issued_jwt_verifies() {
+        let key = IssuerKey::generate();
+        let certificate = key.sign(payload());
+        let jwt = issue_jwt(&key, &certificate, BASE_URL);
+
+        let credential = verify_jwt(&jwt, 1_750_000_000, &revocations(&key, &[])).unwrap();
+        assert_eq!(credential, SkillCredential::from_certificate(&certificate, issuer_did(&key.verifying_key()), BASE_URL));
+        assert_eq!(credential.credential_subject.level, 4);
+        assert_eq!(resolve_did_key(&credential.issuer), Some(key.verifying_key()));
+    }
+
+    #[test]
+    fn validity_window_is_enforced() {
+        let key = IssuerKey::generate();
+        let jwt = issue_jwt(&key, &key.sign(payload()), BASE_URL);
+        assert_eq!(verify_jwt(&jwt, 1_699_999_999, &revocations(&key, &[])), Err(CredentialError::NotYetValid));
+        assert!(verify_jwt(&jwt, 1_700_000_000, &revocations(&key, &[])).is_ok());
+        assert_eq!(verify_jwt(&jwt, 1_800_000_000, &revocations(&key, &[])), Err(CredentialError::Expired));
+    }
+
+    #[test]
+    fn tampered_jwt_is_rejected() {
+        let key = IssuerKey::generate();
+        let jwt = issue_jwt(&key, &key.sign(payload()), BASE_URL);
+
+        let raised = tamper_payload(&jwt, |document| document["credentialSubject"]["level"] = 5.into());
+        assert_eq!(verify_jwt(&raised, 1_750_000_000, &revocations(&key, &[])), Err(CredentialError::InvalidSignature));
+
+        let mut flipped = jwt.clone().into_bytes();
+        let last = flipped.len() - 2;
+        flipped[last] = if flipped[last] == b'A' { b'B' } else { b'A' };
+        assert_eq!(
+            verify_jwt(&String::from_utf8(flipped).unwrap(), 1_750_000_000, &revocations(&key, &[])),
+            Err(CredentialError::InvalidSignature),
+        );
+
+        assert!(matches!(verify_jwt("a.b", 1_750_000_000, &revocations(&key, &[])), Err(CredentialError::Malformed(_))));
+    }
+
+    #[test]
+    fn header_and_issuer_are_checked() {
+        let key = IssuerKey::generate();
+        let other = IssuerKey::generate();
+        let jwt = issue_jwt(&key, &key.sign(payload()), BASE_URL);
+        let rest = jwt.split_once('.').unwrap().1;
+
+        let did = issuer_did(&key.verifying_key());
+        let header = JwtHeader { alg: "none".to_string(), typ: JWT_TYPE.to_string(), kid: did };
+        assert_eq!(
+            verify_jwt(&format!("{}.{}", encode_segment(&header), rest), 1_750_000_000, &revocations(&key, &[])),
+            Err(CredentialError::UnsupportedAlgorithm("none".to_string())),
+        );
+
+        // Re-signed by another key, still naming the original issuer
+        let credential: SkillCredential = decode_jwt(&jwt).unwrap().1;
+        let resigned = sign_jwt(&other, &credential);
+        assert_eq!(verify_jwt(&resigned, 1_750_000_000, &revocations(&key, &[])), Err(CredentialError::IssuerMismatch));
+    }
+
+    #[test]
+    fn revocation_status_is_checked() {
+        let key = IssuerKey::generate();
+        let certificate = key.sign(payload());
+        let jwt = issue_jwt(&key, &certificate, BASE_URL);
+        let status = verify_jwt(&jwt, 1_750_000_000, &revocations(&key, &[])).unwrap().credential_status;
+        assert_eq!(status.revocation_list, "https://ppot.example/certificates/revocations");
+        assert_eq!(status.certificate_id, certificate.payload.certificate_id);
+
+        let revoked = revocations(&key, &[&certificate.payload.certificate_id]);
+        assert_eq!(verify_jwt(&jwt, 1_750_000_000, &revoked), Err(CredentialError::Revoked));
+
+        // A list that doesn't come from the issuer can't vouch for the credential
+        let other = IssuerKey::generate();
+        assert_eq!(verify_jwt(&jwt, 1_750_000_000, &revocations(&other, &[])), Err(CredentialError::InvalidRevocationList));
+    }
+}
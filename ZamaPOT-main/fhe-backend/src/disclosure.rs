@@ -0,0 +1,473 @@
+use ed25519_dalek::VerifyingKey;
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::{BTreeMap, HashSet};
+use std::fmt;
+use crate::certificates::{self, CertificateError, CertificatePayload, IssuerKey};
+
+pub const COMMITMENT_VERSION: u8 = 1;
+// Commitment batches issued at once; each backs a single presentation
+pub const BATCH_COUNT: usize = 8;
+// Longest verifier nonce or audience accepted
+pub const MAX_CHALLENGE_LENGTH: usize = 256;
+// Highest level the contract knows, see `scoring::level_name`
+const MAX_LEVEL: u8 = 5;
+
+/// One salted attribute. The issuer signs only its digest, so the value
+/// stays hidden until the holder discloses it.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct Disclosure {
+    pub salt: String, // hex, 16 random bytes
+    pub name: String,
+    pub value: Value,
+}
+
+/// Digests of every attribute of a certificate, signed by the issuer. The
+/// handle stands in for the certificate id, so presentations don't link back
+/// to the public certificate; each batch has its own.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct AttributeCommitments {
+    pub version: u8,
+    pub handle: String,
+    pub expires_at: Option<u64>, // unix seconds, in the clear so expiry is always checked
+    pub digests: Vec<String>,    // sorted, so their order reveals nothing
+    pub key_id: String,
+    pub signature: String,       // hex
+}
+
+/// Commitments plus every opening under one set of salts.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct CommitmentBatch {
+    pub commitments: AttributeCommitments,
+    pub disclosures: Vec<Disclosure>,
+}
+
+/// Independently salted batches, kept by the registry for the candidate.
+/// Each presentation uses a batch no earlier one used, so two verifiers
+/// can't link their presentations by handle or digests.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct SelectiveCertificate {
+    pub batches: Vec<CommitmentBatch>,
+    pub presented: usize, // batches already used
+}
+
+/// What the candidate asks to reveal, for the verifier's `audience` and
+/// `nonce`. `min_level` discloses only that the level is at least this, not
+/// the level itself.
+#[derive(Debug, Deserialize, Clone, Default)]
+pub struct PresentationRequest {
+    #[serde(default)]
+    pub attributes: Vec<String>,
+    #[serde(default)]
+    pub min_level: Option<u8>,
+    #[serde(default)]
+    pub audience: String,
+    #[serde(default)]
+    pub nonce: String,
+}
+
+/// Ties a presentation to one verifier's challenge so it can't be replayed
+/// to another verifier or reused. Signed by the issuer, which derives
+/// presentations for the holder.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct PresentationBinding {
+    pub audience: String,
+    pub nonce: String,
+    pub key_id: String,
+    pub signature: String, // hex, over the handle, challenge and disclosed digests
+}
+
+/// A derived proof: the signed commitments, the chosen openings and the
+/// challenge they were shown for.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct Presentation {
+    pub commitments: AttributeCommitments,
+    pub disclosures: Vec<Disclosure>,
+    pub binding: PresentationBinding,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum DisclosureError {
+    UnsupportedVersion(u8),
+    UnknownAttribute(String),
+    LevelNotReached(u8),
+    Signature(CertificateError),
+    NotCommitted(String),
+    DuplicateAttribute(String),
+    Expired,
+    MissingChallenge,
+    ChallengeMismatch,
+}
+
+impl fmt::Display for DisclosureError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            DisclosureError::UnsupportedVersion(v) => write!(f, "unsupported commitment version {}", v),
+            DisclosureError::UnknownAttribute(name) => write!(f, "certificate has no attribute {}", name),
+            DisclosureError::LevelNotReached(level) => write!(f, "certificate level is below {}", level),
+            DisclosureError::Signature(e) => write!(f, "{}", e),
+            DisclosureError::NotCommitted(name) => write!(f, "disclosed {} does not match any commitment", name),
+            DisclosureError::DuplicateAttribute(name) => write!(f, "{} is disclosed more than once", name),
+            DisclosureError::Expired => write!(f, "certificate has expired"),
+            DisclosureError::MissingChallenge => {
+                write!(f, "audience and nonce must be 1 to {} characters", MAX_CHALLENGE_LENGTH)
+            }
+            DisclosureError::ChallengeMismatch => write!(f, "presentation was made for another audience or nonce"),
+        }
+    }
+}
+
+fn level_claim(level: u8) -> String {
+    format!("level_at_least_{}", level)
+}
+
+fn random_hex(len: usize) -> String {
+    let mut bytes = vec![0u8; len];
+    rand::thread_rng().fill_bytes(&mut bytes);
+    hex::encode(bytes)
+}
+
+impl Disclosure {
+    fn new(name: &str, value: Value) -> Self {
+        Disclosure { salt: random_hex(16), name: name.to_string(), value }
+    }
+
+    pub fn digest(&self) -> String {
+        let text = format!(
+            "ppot-disclosure\n{}\n{}\n{}",
+            self.salt,
+            certificates::escape_field(&self.name),
+            self.value,
+        );
+        certificates::sha256_hex(text.as_bytes())
+    }
+}
+
+impl AttributeCommitments {
+    pub fn canonical_bytes(&self) -> Vec<u8> {
+        let mut text = format!(
+            "ppot-commitments-v{}\n{}\n{}\n{}",
+            self.version,
+            certificates::escape_field(&self.handle),
+            self.expires_at.map(|t| t.to_string()).unwrap_or_default(),
+            self.digests.len(),
+        );
+        for digest in &self.digests {
+            text.push('\n');
+            text.push_str(digest);
+        }
+        text.into_bytes()
+    }
+}
+
+/// What the binding signature covers: the batch handle, the challenge and
+/// the disclosed digests in sorted order.
+fn binding_bytes(handle: &str, audience: &str, nonce: &str, disclosures: &[Disclosure]) -> Vec<u8> {
+    let mut digests: Vec<String> = disclosures.iter().map(Disclosure::digest).collect();
+    digests.sort();
+    let mut text = format!(
+        "ppot-presentation-v{}\n{}\n{}\n{}\n{}",
+        COMMITMENT_VERSION,
+        certificates::escape_field(handle),
+        certificates::escape_field(audience),
+        certificates::escape_field(nonce),
+        digests.len(),
+    );
+    for digest in &digests {
+        text.push('\n');
+        text.push_str(digest);
+    }
+    text.into_bytes()
+}
+
+/// Commits `BATCH_COUNT` batches for a certificate, see `commit_batch`.
+pub fn commit(key: &IssuerKey, payload: &CertificatePayload) -> SelectiveCertificate {
+    SelectiveCertificate {
+        batches: (0..BATCH_COUNT).map(|_| commit_batch(key, payload)).collect(),
+        presented: 0,
+    }
+}
+
+/// Salts and commits every attribute of a certificate. Each level threshold
+/// is its own boolean claim, so "level ≥ n" can be shown without the level.
+/// The certificate id is left out; it would link presentations to the public record.
+pub fn commit_batch(key: &IssuerKey, payload: &CertificatePayload) -> CommitmentBatch {
+    let mut disclosures = vec![
+        Disclosure::new("skill", json!(payload.quiz_type)),
+        Disclosure::new("level", json!(payload.level)),
+        Disclosure::new("score_commitment", json!(payload.score_commitment)),
+        Disclosure::new("cheating_likelihood_bps", json!(payload.cheating_likelihood_bps)),
+        Disclosure::new("user_id", json!(payload.user_id)),
+        Disclosure::new("issued_at", json!(payload.issued_at)),
+    ];
+    for level in 1..=MAX_LEVEL {
+        disclosures.push(Disclosure::new(&level_claim(level), json!(payload.level >= level)));
+    }
+
+    let mut digests: Vec<String> = disclosures.iter().map(Disclosure::digest).collect();
+    digests.sort();
+    let mut commitments = AttributeCommitments {
+        version: COMMITMENT_VERSION,
+        handle: random_hex(16),
+        expires_at: payload.expires_at,
+        digests,
+        key_id: key.key_id(),
+        signature: String::new(),
+    };
+    commitments.signature = key.sign_bytes(&commitments.canonical_bytes());
+    CommitmentBatch { commitments, disclosures }
+}
+
+impl SelectiveCertificate {
+    /// Marks the next unused batch as presented and returns it, first
+    /// committing a fresh set when every batch has been used.
+    pub fn take_batch(&mut self, key: &IssuerKey, payload: &CertificatePayload) -> CommitmentBatch {
+        if self.presented >= self.batches.len() {
+            self.batches.extend(commit(key, payload).batches);
+        }
+        self.presented += 1;
+        self.batches[self.presented - 1].clone()
+    }
+}
+
+fn valid_challenge(value: &str) -> bool {
+    !value.is_empty() && value.len() <= MAX_CHALLENGE_LENGTH
+}
+
+impl CommitmentBatch {
+    /// Derives a presentation opening only the requested attributes, bound
+    /// to the request's audience and nonce.
+    pub fn present(&self, key: &IssuerKey, request: &PresentationRequest) -> Result<Presentation, DisclosureError> {
+        if !valid_challenge(&request.audience) || !valid_challenge(&request.nonce) {
+            return Err(DisclosureError::MissingChallenge);
+        }
+        let mut names = request.attributes.clone();
+        if let Some(level) = request.min_level {
+            names.push(level_claim(level));
+        }
+
+        let mut disclosures = Vec::new();
+        for name in names {
+            if disclosures.iter().any(|d: &Disclosure| d.name == name) {
+                continue;
+            }
+            let disclosure = self.disclosures.iter().find(|d| d.name == name)
+                .ok_or_else(|| DisclosureError::UnknownAttribute(name.clone()))?;
+            disclosures.push(disclosure.clone());
+        }
+        if let Some(level) = request.min_level {
+            let reached = disclosures.iter()
+                .any(|d| d.name == level_claim(level) && d.value == Value::Bool(true));
+            if !reached {
+                return Err(DisclosureError::LevelNotReached(level));
+            }
+        }
+
+        let signed = binding_bytes(&self.commitments.handle, &request.audience, &request.nonce, &disclosures);
+        Ok(Presentation {
+            commitments: self.commitments.clone(),
+            disclosures,
+            binding: PresentationBinding {
+                audience: request.audience.clone(),
+                nonce: request.nonce.clone(),
+                key_id: key.key_id(),
+                signature: key.sign_bytes(&signed),
+            },
+        })
+    }
+}
+
+/// Checks a presentation against an issuer public key and the verifier's
+/// own `audience` and `nonce`, and returns the disclosed attributes.
+/// Revocation needs the issuer's registry.
+pub fn verify(
+    key: &VerifyingKey,
+    presentation: &Presentation,
+    audience: &str,
+    nonce: &str,
+    now: u64,
+) -> Result<BTreeMap<String, Value>, DisclosureError> {
+    let commitments = &presentation.commitments;
+    if commitments.version != COMMITMENT_VERSION {
+        return Err(DisclosureError::UnsupportedVersion(commitments.version));
+    }
+    certificates::verify_bytes(key, &commitments.key_id, &commitments.canonical_bytes(), &commitments.signature)
+        .map_err(DisclosureError::Signature)?;
+    let binding = &presentation.binding;
+    if binding.audience != audience || binding.nonce != nonce {
+        return Err(DisclosureError::ChallengeMismatch);
+    }
+    if commitments.expires_at.is_some_and(|expires_at| now >= expires_at) {
+        return Err(DisclosureError::Expired);
+    }
+
+    let digests: HashSet<&str> = commitments.digests.iter().map(String::as_str).collect();
+    let mut attributes = BTreeMap::new();
+    for disclosure in &presentation.disclosures {
+        if !digests.contains(disclosure.digest().as_str()) {
+            return Err(DisclosureError::NotCommitted(disclosure.name.clone()));
+        }
+        if attributes.insert(disclosure.name.clone(), disclosure.value.clone()).is_some() {
+            return Err(DisclosureError::DuplicateAttribute(disclosure.name.clone()));
+        }
+    }
+    // Openings taken from another presentation don't carry its binding
+    let signed = binding_bytes(&commitments.handle, audience, nonce, &presentation.disclosures);
+    certificates::verify_bytes(key, &binding.key_id, &signed, &binding.signature)
+        .map_err(DisclosureError::Signature)?;
+    Ok(attributes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::certificates::tests::payload;
+
+    const AUDIENCE: &str = "https://verifier.example";
+    const NONCE: &str = "4f1d2c";
+
+    fn request(attributes: &[&str], min_level: Option<u8>) -> PresentationRequest {
+        PresentationRequest {
+            attributes: attributes.iter().map(|name| name.to_string()).collect(),
+            min_level,
+            audience: AUDIENCE.to_string(),
+            nonce: NONCE.to_string(),
+        }
+    }
+
+    fn batch(key: &IssuerKey) -> CommitmentBatch {
+        commit_batch(key, &payload())
+    }
+
+    #[test]
+    fn presentation_discloses_only_what_was_asked() {
+        let key = IssuerKey::generate();
+        let certificate = batch(&key);
+        assert_eq!(certificate.commitments.digests.len(), 6 + MAX_LEVEL as usize);
+        assert!(certificate.disclosures.iter().all(|d| d.name != "certificate_id"));
+
+        let presentation = certificate.present(&key, &request(&["skill"], Some(3))).unwrap();
+        let attributes = verify(&key.verifying_key(), &presentation, AUDIENCE, NONCE, 1_750_000_000).unwrap();
+        assert_eq!(attributes.len(), 2);
+        assert_eq!(attributes["skill"], json!("math"));
+        assert_eq!(attributes["level_at_least_3"], json!(true));
+    }
+
+    #[test]
+    fn level_claims_follow_the_level() {
+        let key = IssuerKey::generate();
+        let certificate = batch(&key);
+        assert!(certificate.present(&key, &request(&[], Some(4))).is_ok());
+        assert_eq!(certificate.present(&key, &request(&[], Some(5))), Err(DisclosureError::LevelNotReached(5)));
+        assert_eq!(
+            certificate.present(&key, &request(&["certificate_id"], None)),
+            Err(DisclosureError::UnknownAttribute("certificate_id".to_string())),
+        );
+    }
+
+    #[test]
+    fn tampered_presentation_is_rejected() {
+        let key = IssuerKey::generate();
+        let presentation = batch(&key).present(&key, &request(&["skill", "level"], None)).unwrap();
+        let now = 1_750_000_000;
+
+        let mut edited = presentation.clone();
+        edited.disclosures[0].value = json!("security");
+        assert_eq!(verify(&key.verifying_key(), &edited, AUDIENCE, NONCE, now), Err(DisclosureError::NotCommitted("skill".to_string())));
+
+        let mut duplicated = presentation.clone();
+        duplicated.disclosures.push(presentation.disclosures[0].clone());
+        assert_eq!(
+            verify(&key.verifying_key(), &duplicated, AUDIENCE, NONCE, now),
+            Err(DisclosureError::DuplicateAttribute("skill".to_string())),
+        );
+
+        let mut extended = presentation.clone();
+        extended.commitments.expires_at = None;
+        assert_eq!(
+            verify(&key.verifying_key(), &extended, AUDIENCE, NONCE, now),
+            Err(DisclosureError::Signature(CertificateError::InvalidSignature)),
+        );
+
+        let mut injected = presentation.clone();
+        let forged = Disclosure::new("level_at_least_5", json!(true));
+        injected.commitments.digests.push(forged.digest());
+        injected.disclosures.push(forged);
+        assert_eq!(
+            verify(&key.verifying_key(), &injected, AUDIENCE, NONCE, now),
+            Err(DisclosureError::Signature(CertificateError::InvalidSignature)),
+        );
+
+        let mut future = presentation;
+        future.commitments.version = COMMITMENT_VERSION + 1;
+        assert_eq!(
+            verify(&key.verifying_key(), &future, AUDIENCE, NONCE, now),
+            Err(DisclosureError::UnsupportedVersion(COMMITMENT_VERSION + 1)),
+        );
+    }
+
+    #[test]
+    fn expired_commitments_are_rejected() {
+        let key = IssuerKey::generate();
+        let presentation = batch(&key).present(&key, &request(&["skill"], None)).unwrap();
+        assert!(verify(&key.verifying_key(), &presentation, AUDIENCE, NONCE, 1_799_999_999).is_ok());
+        assert_eq!(verify(&key.verifying_key(), &presentation, AUDIENCE, NONCE, 1_800_000_000), Err(DisclosureError::Expired));
+        assert_eq!(
+            verify(&IssuerKey::generate().verifying_key(), &presentation, AUDIENCE, NONCE, 1_750_000_000),
+            Err(DisclosureError::Signature(CertificateError::UnknownKey(key.key_id()))),
+        );
+    }
+
+    #[test]
+    fn presentations_are_bound_to_the_verifier_challenge() {
+        let key = IssuerKey::generate();
+        let certificate = batch(&key);
+        let verifying_key = key.verifying_key();
+        let now = 1_750_000_000;
+
+        let mut unbound = request(&["skill"], None);
+        unbound.nonce.clear();
+        assert_eq!(certificate.present(&key, &unbound), Err(DisclosureError::MissingChallenge));
+
+        let presentation = certificate.present(&key, &request(&["skill"], None)).unwrap();
+        assert!(verify(&verifying_key, &presentation, AUDIENCE, NONCE, now).is_ok());
+        assert_eq!(
+            verify(&verifying_key, &presentation, "https://other.example", NONCE, now),
+            Err(DisclosureError::ChallengeMismatch),
+        );
+        assert_eq!(verify(&verifying_key, &presentation, AUDIENCE, "fresh", now), Err(DisclosureError::ChallengeMismatch));
+
+        // Rewriting the challenge breaks the binding signature
+        let mut replayed = presentation.clone();
+        replayed.binding.nonce = "fresh".to_string();
+        assert_eq!(
+            verify(&verifying_key, &replayed, AUDIENCE, "fresh", now),
+            Err(DisclosureError::Signature(CertificateError::InvalidSignature)),
+        );
+
+        // Openings from a wider presentation can't be added to a narrower one
+        let wider = certificate.present(&key, &request(&["skill", "level"], None)).unwrap();
+        let mut spliced = presentation;
+        spliced.disclosures = wider.disclosures;
+        assert_eq!(
+            verify(&verifying_key, &spliced, AUDIENCE, NONCE, now),
+            Err(DisclosureError::Signature(CertificateError::InvalidSignature)),
+        );
+    }
+
+    #[test]
+    fn each_presentation_uses_a_fresh_batch() {
+        let key = IssuerKey::generate();
+        let mut certificate = commit(&key, &payload());
+        let batches: Vec<CommitmentBatch> = (0..BATCH_COUNT + 1)
+            .map(|_| certificate.take_batch(&key, &payload()))
+            .collect();
+        assert_eq!((certificate.presented, certificate.batches.len()), (BATCH_COUNT + 1, 2 * BATCH_COUNT));
+
+        let handles: HashSet<&str> = batches.iter().map(|b| b.commitments.handle.as_str()).collect();
+        let digests: HashSet<&str> = batches.iter().flat_map(|b| &b.commitments.digests).map(String::as_str).collect();
+        assert_eq!(handles.len(), batches.len());
+        assert_eq!(digests.len(), batches.len() * batches[0].commitments.digests.len());
+    }
+}
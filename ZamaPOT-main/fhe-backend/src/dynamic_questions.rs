@@ -1,6 +1,13 @@
 use serde::{Deserialize, Serialize};
-use rand::{Rng, SeedableRng, rngs::StdRng};
+use rand::{Rng, RngCore, SeedableRng, rngs::StdRng};
 use std::collections::HashMap;
+use crate::telemetry::TelemetrySummary;
+use crate::answer_patterns::AnswerPattern;
+use crate::behavior::{BehaviorData, ObservedBehavior};
+use crate::cheating_model::{CheatingModel, DetectorSignals};
+use crate::accommodations::Accommodation;
+use crate::adaptive::{AdaptiveConfig, AdaptiveState};
+use crate::item_stats::ItemStatistics;
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct DynamicQuestion {
@@ -8,17 +15,28 @@ pub struct DynamicQuestion {
     pub question_text: String,
     pub options: Vec<String>,
     pub correct_answer: u8,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub part_answers: Vec<u8>, // correct option of each part, for multi-part items
     pub parameters: HashMap<String, String>,
     pub difficulty: f32,
     pub expected_time: u32, // in seconds
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub calibration_version: Option<u32>, // template calibration applied when served
 }
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct UserSession {
     pub user_id: String,
+    pub quiz_type: String,
     pub questions: Vec<DynamicQuestion>,
     pub start_time: u64,
     pub behavior_metrics: BehaviorMetrics,
+    pub accommodation: Accommodation,
+    pub adaptive: Option<AdaptiveState>,
+    #[serde(default)]
+    pub key_id: Option<String>, // client key the answers must be encrypted under
+    #[serde(default)]
+    pub score_salt: String, // hex, 128 bits drawn once so the score commitment is fixed per session
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -26,6 +44,10 @@ pub struct BehaviorMetrics {
     pub answer_times: Vec<u32>, // time per question in seconds
     pub switch_count: u32,      // how many times user changed answers
     pub consistency_score: f32, // pattern consistency
+    pub time_consistency: f32,
+    pub pattern_deviation: f32,
+    pub telemetry: TelemetrySummary,
+    pub answer_pattern: Option<AnswerPattern>,
 }
 
 impl DynamicQuestion {
@@ -154,9 +176,11 @@ impl DynamicQuestion {
             question_text,
             options,
             correct_answer: correct_index,
+            part_answers: Vec::new(),
             parameters: params,
             difficulty,
-            expected_time: 30, // 30 seconds expected
+            expected_time: 30,
+            calibration_version: None, // 30 seconds expected
         }
     }
 
@@ -237,9 +261,31 @@ impl DynamicQuestion {
             question_text,
             options,
             correct_answer: correct_index,
+            part_answers: Vec::new(),
             parameters: params,
             difficulty,
             expected_time: 45,
+            calibration_version: None,
+        }
+    }
+
+    pub fn generate(quiz_type: &str, user_id: &str, difficulty: f32) -> Self {
+        match quiz_type {
+            "math" => DynamicQuestion::generate_math_question(user_id, difficulty),
+            "programming" => DynamicQuestion::generate_programming_question(user_id, difficulty),
+            "blockchain" => DynamicQuestion::generate_blockchain_question(user_id, difficulty),
+            "security" => DynamicQuestion::generate_security_question(user_id, difficulty),
+            _ => DynamicQuestion::generate_math_question(user_id, difficulty)
+        }
+    }
+
+    /// Correct option of each answer part. Single-part items have one, and
+    /// a submitted answer must carry exactly as many parts as this.
+    pub fn answer_key(&self) -> &[u8] {
+        if self.part_answers.is_empty() {
+            std::slice::from_ref(&self.correct_answer)
+        } else {
+            &self.part_answers
         }
     }
 
@@ -265,23 +311,21 @@ impl DynamicQuestion {
 
 impl UserSession {
     pub fn new(user_id: String, quiz_type: &str, question_count: usize) -> Self {
+        Self::with_starting_difficulty(user_id, quiz_type, question_count, 0.3)
+    }
+
+    /// Like `new`, but the difficulty ramp starts at `starting_difficulty`.
+    pub fn with_starting_difficulty(user_id: String, quiz_type: &str, question_count: usize, starting_difficulty: f32) -> Self {
         let mut questions = Vec::new();
-        let mut rng = rand::thread_rng();
         
         for i in 0..question_count {
-            let difficulty = 0.3 + (i as f32 * 0.2); // Increasing difficulty
-            let question = match quiz_type {
-                "math" => DynamicQuestion::generate_math_question(&user_id, difficulty),
-                "programming" => DynamicQuestion::generate_programming_question(&user_id, difficulty),
-                "blockchain" => DynamicQuestion::generate_blockchain_question(&user_id, difficulty),
-                "security" => DynamicQuestion::generate_security_question(&user_id, difficulty),
-                _ => DynamicQuestion::generate_math_question(&user_id, difficulty)
-            };
-            questions.push(question);
+            let difficulty = (starting_difficulty + i as f32 * 0.2).min(1.0); // Increasing difficulty
+            questions.push(DynamicQuestion::generate(quiz_type, &user_id, difficulty));
         }
 
         UserSession {
             user_id,
+            quiz_type: quiz_type.to_string(),
             questions,
             start_time: std::time::SystemTime::now()
                 .duration_since(std::time::UNIX_EPOCH)
@@ -291,10 +335,50 @@ impl UserSession {
                 answer_times: Vec::new(),
                 switch_count: 0,
                 consistency_score: 1.0,
+                time_consistency: 1.0,
+                pattern_deviation: 0.0,
+                telemetry: TelemetrySummary::default(),
+                answer_pattern: None,
+            },
+            accommodation: Accommodation::default(),
+            adaptive: None,
+            key_id: None,
+            score_salt: {
+                let mut salt = [0u8; 16];
+                rand::rngs::OsRng.fill_bytes(&mut salt);
+                hex::encode(salt)
             },
         }
     }
 
+    /// Starts an adaptive session with a single item at `starting_difficulty`;
+    /// later items are served one at a time as answers come in.
+    pub fn new_adaptive(user_id: String, quiz_type: &str, config: AdaptiveConfig, starting_difficulty: f32) -> Self {
+        let mut session = UserSession::new(user_id, quiz_type, 0);
+        session.questions.push(DynamicQuestion::generate(quiz_type, &session.user_id, starting_difficulty));
+        session.adaptive = Some(AdaptiveState::new(config, crate::irt::logit_difficulty(starting_difficulty)));
+        session
+    }
+
+    /// Generates the next adaptive item, matched to the current ability estimate.
+    pub fn serve_next_question(&mut self, item_stats: &ItemStatistics) -> Option<&DynamicQuestion> {
+        let difficulty = self.adaptive.as_ref().filter(|state| !state.finished)?.next_difficulty();
+        let mut question = DynamicQuestion::generate(&self.quiz_type, &self.user_id, difficulty);
+        item_stats.apply_calibration(&self.quiz_type, &mut question);
+        question.expected_time = self.accommodation.scale_expected_time(question.expected_time);
+        self.questions.push(question);
+        self.questions.last()
+    }
+
+    /// Applies a candidate's accommodation, extending each question's expected time.
+    pub fn with_accommodation(mut self, accommodation: Accommodation) -> Self {
+        for question in &mut self.questions {
+            question.expected_time = accommodation.scale_expected_time(question.expected_time);
+        }
+        self.accommodation = accommodation;
+        self
+    }
+
     pub fn add_answer_time(&mut self, time_seconds: u32) {
         self.behavior_metrics.answer_times.push(time_seconds);
     }
@@ -303,51 +387,51 @@ impl UserSession {
         self.behavior_metrics.switch_count += 1;
     }
 
-    pub fn calculate_cheating_likelihood(&self) -> f32 {
-        let metrics = &self.behavior_metrics;
-        
-        if metrics.answer_times.is_empty() {
-            return 0.0;
-        }
+    pub fn record_consistency(&mut self, consistency_score: f32) {
+        self.behavior_metrics.consistency_score = consistency_score.clamp(0.0, 1.0);
+    }
 
-        let mut cheating_score: f32 = 0.0;
+    pub fn record_timing_profile(&mut self, time_consistency: f32, pattern_deviation: f32) {
+        self.behavior_metrics.time_consistency = time_consistency;
+        self.behavior_metrics.pattern_deviation = pattern_deviation;
+    }
 
-        // 1. Check for extremely fast answers (likely cheating)
-        let avg_time: f32 = metrics.answer_times.iter().sum::<u32>() as f32 / metrics.answer_times.len() as f32;
-        let fast_answers = metrics.answer_times.iter().filter(|&&t| t < 3).count() as f32;
-        if fast_answers > metrics.answer_times.len() as f32 * 0.5 {
-            cheating_score += 0.4;
-        }
+    pub fn record_answer_pattern(&mut self, pattern: Option<AnswerPattern>) {
+        self.behavior_metrics.answer_pattern = pattern;
+    }
 
-        // 2. Check for inconsistent timing patterns
-        let time_variance: f32 = metrics.answer_times.iter()
-            .map(|&t| (t as f32 - avg_time).powi(2))
-            .sum::<f32>() / metrics.answer_times.len() as f32;
-        
-        if time_variance < 1.0 { // Too consistent (bot-like)
-            cheating_score += 0.3;
-        }
+    pub fn record_telemetry(&mut self, summary: TelemetrySummary) {
+        self.behavior_metrics.telemetry = summary;
+    }
 
-        // 3. Check for excessive answer switching
-        let switch_ratio = metrics.switch_count as f32 / self.questions.len() as f32;
-        if switch_ratio > 0.8 { // Too much switching
-            cheating_score += 0.2;
+    pub fn record_behavior(&mut self, data: &BehaviorData, observed: &ObservedBehavior) {
+        for &time in &data.answer_times {
+            self.add_answer_time(time);
         }
-
-        // 4. Check for perfect scores with very fast times
-        let perfect_fast = avg_time < 5.0 && metrics.answer_times.len() == self.questions.len();
-        if perfect_fast {
-            cheating_score += 0.3;
+        if !self.accommodation.ignore_switches {
+            for &switches in &data.switch_counts {
+                if switches > 0 {
+                    self.increment_switch_count();
+                }
+            }
         }
+        self.record_telemetry(observed.telemetry.clone());
+        self.record_answer_pattern(observed.answer_pattern.clone());
+    }
+
+    pub fn detector_signals(&self) -> DetectorSignals {
+        DetectorSignals::from_metrics(&self.behavior_metrics, self.questions.len(), &self.accommodation)
+    }
 
-        cheating_score.min(1.0)
+    pub fn calculate_cheating_likelihood(&self, model: &CheatingModel) -> f32 {
+        model.likelihood(&self.detector_signals())
     }
 }
 
 impl DynamicQuestion {
     pub fn generate_blockchain_question(user_id: &str, difficulty: f32) -> Self {
         let mut rng = rand::thread_rng();
-        let questions = vec![
+        let questions = [
             "What is the main purpose of a smart contract?",
             "Which consensus mechanism does Ethereum currently use?",
             "What does 'gas' represent in Ethereum?",
@@ -380,15 +464,17 @@ impl DynamicQuestion {
             question_text,
             options,
             correct_answer: correct_index,
+            part_answers: Vec::new(),
             parameters: HashMap::new(),
             difficulty,
             expected_time: 40,
+            calibration_version: None,
         }
     }
 
     pub fn generate_security_question(user_id: &str, difficulty: f32) -> Self {
         let mut rng = rand::thread_rng();
-        let questions = vec![
+        let questions = [
             "What is the primary goal of encryption?",
             "What does 2FA help protect against?",
             "What is a common phishing attack method?",
@@ -421,9 +507,11 @@ impl DynamicQuestion {
             question_text,
             options,
             correct_answer: correct_index,
+            part_answers: Vec::new(),
             parameters: HashMap::new(),
             difficulty,
             expected_time: 35,
+            calibration_version: None,
         }
     }
 }
@@ -0,0 +1,347 @@
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use crate::attestation::{self, BadgeMinted};
+use crate::registry::CertificateRegistry;
+use crate::rpc::{self, RpcClient, RpcError};
+
+// Many public nodes cap eth_getLogs ranges
+const MAX_BLOCK_RANGE: u64 = 2_000;
+
+#[derive(Debug, Clone)]
+pub struct IndexerConfig {
+    pub rpc_url: String,
+    pub contract: [u8; 20],
+    pub start_block: u64,
+    pub poll_interval: Duration,
+}
+
+/// An indexed mint with the on-chain score it recorded.
+#[derive(Debug, Serialize, Clone)]
+pub struct IndexedBadge {
+    pub event: BadgeMinted,
+    pub encrypted_score: Option<String>, // from talentRecords, None if the call failed
+    pub block_number: u64,
+    pub tx_hash: String,
+}
+
+/// Why a mint does not line up with what the backend issued.
+#[derive(Debug, Serialize, Clone, PartialEq)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum MintFinding {
+    UnknownCertificate,
+    ScoreMismatch { expected: String, actual: Option<String> },
+    SkillMismatch { issued: String },
+    LevelMismatch { issued: u8 },
+    CheatingLikelihoodMismatch { issued: u8 },
+    NotPassed,
+    Revoked,
+    DuplicateMint { other_tokens: Vec<u64> },
+    TokenMismatch { recorded: u64 }, // registry recorded a different relayed token
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct SuspiciousToken {
+    pub token_id: u64,
+    pub owner: String,
+    pub certificate_id: String,
+    pub block_number: u64,
+    pub tx_hash: String,
+    pub findings: Vec<MintFinding>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct IndexerReport {
+    pub indexed_block: Option<u64>,
+    pub indexed_tokens: usize,
+    pub suspicious_tokens: Vec<SuspiciousToken>,
+    pub last_error: Option<String>,
+}
+
+#[derive(Debug, Default)]
+struct IndexerState {
+    next_block: u64,
+    badges: BTreeMap<u64, IndexedBadge>, // by token id
+    last_error: Option<String>,
+}
+
+/// Follows `BadgeMinted` logs and checks each mint against the certificate registry.
+pub struct ChainIndexer {
+    state: Arc<Mutex<IndexerState>>,
+    registry: Arc<CertificateRegistry>,
+}
+
+fn parse_bytes32(value: &Value) -> Option<[u8; 32]> {
+    hex::decode(value.as_str()?.strip_prefix("0x")?).ok()?.try_into().ok()
+}
+
+fn fetch_score(client: &RpcClient, contract: &str, token_id: u64) -> Result<Option<String>, RpcError> {
+    let call = json!({
+        "to": contract,
+        "data": attestation::to_hex(&attestation::talent_record_calldata(token_id)),
+    });
+    let result = client.call("eth_call", json!([call, "latest"]))?;
+    let data = result.as_str()
+        .and_then(|s| hex::decode(s.trim_start_matches("0x")).ok())
+        .unwrap_or_default();
+    Ok(attestation::decode_record_score(&data).map(|score| attestation::to_hex(&score)))
+}
+
+/// Indexes up to one block range; returns how many badges were added.
+fn poll(client: &RpcClient, config: &IndexerConfig, state: &Mutex<IndexerState>) -> Result<usize, RpcError> {
+    let from = state.lock().unwrap().next_block;
+    let latest = client.block_number()?;
+    if from > latest {
+        return Ok(0);
+    }
+    let to = latest.min(from + MAX_BLOCK_RANGE - 1);
+
+    let contract = attestation::to_hex(&config.contract);
+    let filter = json!({
+        "address": contract,
+        "fromBlock": rpc::quantity(from as u128),
+        "toBlock": rpc::quantity(to as u128),
+        "topics": [attestation::badge_minted_topic()],
+    });
+    let logs = client.call("eth_getLogs", json!([filter]))?;
+
+    let mut badges = Vec::new();
+    for log in logs.as_array().into_iter().flatten() {
+        let topics: Vec<[u8; 32]> = log["topics"].as_array().into_iter().flatten()
+            .filter_map(parse_bytes32)
+            .collect();
+        let data = log["data"].as_str()
+            .and_then(|s| hex::decode(s.trim_start_matches("0x")).ok())
+            .unwrap_or_default();
+        let Some(event) = BadgeMinted::decode(&topics, &data) else {
+            println!("⚠️  Skipping undecodable BadgeMinted log in tx {}", log["transactionHash"]);
+            continue;
+        };
+        badges.push(IndexedBadge {
+            encrypted_score: fetch_score(client, &contract, event.token_id)?,
+            block_number: rpc::parse_quantity(&log["blockNumber"])?,
+            tx_hash: log["transactionHash"].as_str().unwrap_or_default().to_string(),
+            event,
+        });
+    }
+
+    // Only advance once the whole range is fetched, so failures are retried
+    let mut state = state.lock().unwrap();
+    let added = badges.len();
+    for badge in badges {
+        state.badges.insert(badge.event.token_id, badge);
+    }
+    state.next_block = to + 1;
+    Ok(added)
+}
+
+impl ChainIndexer {
+    pub fn start(config: IndexerConfig, registry: Arc<CertificateRegistry>) -> Self {
+        let state = Arc::new(Mutex::new(IndexerState {
+            next_block: config.start_block,
+            ..Default::default()
+        }));
+
+        let worker_state = state.clone();
+        std::thread::spawn(move || {
+            let client = RpcClient::new(&config.rpc_url);
+            loop {
+                match poll(&client, &config, &worker_state) {
+                    Ok(added) => {
+                        if added > 0 {
+                            println!("🔎 Indexed {} BadgeMinted event(s)", added);
+                        }
+                        worker_state.lock().unwrap().last_error = None;
+                        // Keep going without sleeping while catching up
+                        let caught_up = client.block_number()
+                            .map(|latest| worker_state.lock().unwrap().next_block > latest)
+                            .unwrap_or(true);
+                        if !caught_up {
+                            continue;
+                        }
+                    }
+                    Err(e) => {
+                        println!("⚠️  Chain indexer poll failed: {}", e);
+                        worker_state.lock().unwrap().last_error = Some(e.to_string());
+                    }
+                }
+                std::thread::sleep(config.poll_interval);
+            }
+        });
+
+        ChainIndexer { state, registry }
+    }
+
+    fn findings(&self, badge: &IndexedBadge, mints_per_certificate: &HashMap<&str, Vec<u64>>) -> Vec<MintFinding> {
+        let event = &badge.event;
+        let mut findings = Vec::new();
+
+        let others: Vec<u64> = mints_per_certificate.get(event.certificate_id.as_str())
+            .into_iter().flatten()
+            .copied()
+            .filter(|&token_id| token_id != event.token_id)
+            .collect();
+        if !others.is_empty() {
+            findings.push(MintFinding::DuplicateMint { other_tokens: others });
+        }
+
+        let Some(entry) = self.registry.entry(&event.certificate_id) else {
+            findings.push(MintFinding::UnknownCertificate);
+            return findings;
+        };
+        let payload = &entry.certificate.payload;
+
+        let expected = attestation::to_hex(&attestation::score_commitment(
+            &payload.certificate_id,
+            &payload.score_commitment,
+        ));
+        if badge.encrypted_score.as_ref() != Some(&expected) {
+            findings.push(MintFinding::ScoreMismatch { expected, actual: badge.encrypted_score.clone() });
+        }
+        if event.skill_type != payload.quiz_type {
+            findings.push(MintFinding::SkillMismatch { issued: payload.quiz_type.clone() });
+        }
+        if event.level != payload.level {
+            findings.push(MintFinding::LevelMismatch { issued: payload.level });
+        }
+        let issued_likelihood = (payload.cheating_likelihood_bps / 100).min(100) as u8;
+        if event.cheating_likelihood != issued_likelihood {
+            findings.push(MintFinding::CheatingLikelihoodMismatch { issued: issued_likelihood });
+        }
+        if !entry.passed {
+            findings.push(MintFinding::NotPassed);
+        }
+        if entry.revocation.is_some() {
+            findings.push(MintFinding::Revoked);
+        }
+        if let Some(recorded) = entry.token_id.filter(|&recorded| recorded != event.token_id) {
+            findings.push(MintFinding::TokenMismatch { recorded });
+        }
+        findings
+    }
+
+    /// Checks every indexed mint against the registry as it stands now,
+    /// so later revocations show up too.
+    pub fn report(&self) -> IndexerReport {
+        let state = self.state.lock().unwrap();
+        let mut mints_per_certificate: HashMap<&str, Vec<u64>> = HashMap::new();
+        for badge in state.badges.values() {
+            mints_per_certificate.entry(badge.event.certificate_id.as_str()).or_default()
+                .push(badge.event.token_id);
+        }
+
+        let suspicious_tokens = state.badges.values()
+            .filter_map(|badge| {
+                let findings = self.findings(badge, &mints_per_certificate);
+                (!findings.is_empty()).then(|| SuspiciousToken {
+                    token_id: badge.event.token_id,
+                    owner: badge.event.user.clone(),
+                    certificate_id: badge.event.certificate_id.clone(),
+                    block_number: badge.block_number,
+                    tx_hash: badge.tx_hash.clone(),
+                    findings,
+                })
+            })
+            .collect();
+
+        IndexerReport {
+            indexed_block: state.next_block.checked_sub(1),
+            indexed_tokens: state.badges.len(),
+            suspicious_tokens,
+            last_error: state.last_error.clone(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::certificates::{tests::payload, IssuerKey};
+    use crate::disclosure;
+
+    fn indexer(badges: Vec<IndexedBadge>) -> ChainIndexer {
+        let key = IssuerKey::generate();
+        let registry = CertificateRegistry::default();
+        let certificate = key.sign(payload());
+        let selective = disclosure::commit(&key, &certificate.payload);
+        registry.register(certificate, selective, "holder-secret", true).unwrap();
+        let state = IndexerState {
+            next_block: 101,
+            badges: badges.into_iter().map(|badge| (badge.event.token_id, badge)).collect(),
+            last_error: None,
+        };
+        ChainIndexer { state: Arc::new(Mutex::new(state)), registry: Arc::new(registry) }
+    }
+
+    fn mint(token_id: u64) -> IndexedBadge {
+        let payload = payload();
+        IndexedBadge {
+            encrypted_score: Some(attestation::to_hex(&attestation::score_commitment(
+                &payload.certificate_id,
+                &payload.score_commitment,
+            ))),
+            event: BadgeMinted {
+                user: "0x0000000000000000000000000000000000000001".to_string(),
+                token_id,
+                skill_type: payload.quiz_type,
+                level: payload.level,
+                certificate_id: payload.certificate_id,
+                cheating_likelihood: 12,
+                behavior_flagged: false,
+                timestamp: payload.issued_at,
+            },
+            block_number: 100,
+            tx_hash: format!("0x{:064x}", token_id),
+        }
+    }
+
+    fn findings(indexer: &ChainIndexer) -> Vec<(u64, Vec<MintFinding>)> {
+        indexer.report().suspicious_tokens.into_iter()
+            .map(|token| (token.token_id, token.findings))
+            .collect()
+    }
+
+    #[test]
+    fn genuine_mint_is_not_suspicious() {
+        let indexer = indexer(vec![mint(1)]);
+        let report = indexer.report();
+        assert_eq!((report.indexed_block, report.indexed_tokens), (Some(100), 1));
+        assert!(report.suspicious_tokens.is_empty());
+    }
+
+    #[test]
+    fn mints_that_differ_from_the_certificate_are_reported() {
+        let mut forged = mint(1);
+        forged.encrypted_score = Some(attestation::to_hex(&[0x22; 32]));
+        forged.event.level = 5;
+        let expected = mint(1).encrypted_score.unwrap();
+        assert_eq!(findings(&indexer(vec![forged])), [(1, vec![
+            MintFinding::ScoreMismatch { expected, actual: Some(attestation::to_hex(&[0x22; 32])) },
+            MintFinding::LevelMismatch { issued: 4 },
+        ])]);
+
+        let mut unknown = mint(2);
+        unknown.event.certificate_id = "CERT_ffffffffffffffffffffffffffffffff".to_string();
+        assert_eq!(findings(&indexer(vec![unknown])), [(2, vec![MintFinding::UnknownCertificate])]);
+    }
+
+    #[test]
+    fn duplicate_mints_and_later_revocations_are_reported() {
+        let indexer = indexer(vec![mint(1), mint(2)]);
+        assert_eq!(findings(&indexer), [
+            (1, vec![MintFinding::DuplicateMint { other_tokens: vec![2] }]),
+            (2, vec![MintFinding::DuplicateMint { other_tokens: vec![1] }]),
+        ]);
+
+        let certificate_id = payload().certificate_id;
+        indexer.registry.record_token(&certificate_id, 1).unwrap();
+        indexer.registry.revoke(&certificate_id, "test".to_string(), 1_750_000_000).unwrap();
+        assert_eq!(findings(&indexer)[1], (2, vec![
+            MintFinding::DuplicateMint { other_tokens: vec![1] },
+            MintFinding::Revoked,
+            MintFinding::TokenMismatch { recorded: 1 },
+        ]));
+    }
+}
@@ -0,0 +1,159 @@
+use serde::{Deserialize, Serialize};
+
+// Profiles need a few attempts before deviations mean anything
+pub const MIN_PROFILE_SAMPLES: u32 = 3;
+// Mean absolute z-score above which an attempt looks like a different person
+pub const IMPERSONATION_DEVIATION: f32 = 3.0;
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct KeystrokeSummary {
+    pub key_count: u32,
+    pub mean_dwell_ms: f32,   // key down to key up
+    pub mean_flight_ms: f32,  // key up to next key down
+    pub flight_std_ms: f32,
+    pub backspace_count: u32,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct PointerSummary {
+    pub move_count: u32,
+    pub mean_speed: f32,      // pixels per millisecond
+    pub straightness: f32,    // straight-line distance / path length, 0..1
+    pub click_count: u32,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct InputSample {
+    pub keystrokes: Option<KeystrokeSummary>,
+    pub pointer: Option<PointerSummary>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Default)]
+pub struct RunningStat {
+    pub count: u32,
+    pub mean: f32,
+    pub m2: f32,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct InputProfile {
+    pub samples: u32,
+    pub dwell: RunningStat,
+    pub flight: RunningStat,
+    pub flight_std: RunningStat,
+    pub pointer_speed: RunningStat,
+    pub straightness: RunningStat,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct InputConsistency {
+    pub deviation: f32,
+    pub profile_samples: u32,
+    pub impersonation_suspected: bool,
+}
+
+impl RunningStat {
+    pub fn push(&mut self, value: f32) {
+        self.count += 1;
+        let delta = value - self.mean;
+        self.mean += delta / self.count as f32;
+        self.m2 += delta * (value - self.mean);
+    }
+
+    pub fn std_dev(&self) -> f32 {
+        if self.count < 2 {
+            return 0.0;
+        }
+        (self.m2 / (self.count - 1) as f32).sqrt()
+    }
+
+    /// Absolute z-score of `value`, or None while the stat has too few samples.
+    /// A small floor on the deviation keeps near-identical histories from
+    /// turning tiny differences into huge scores.
+    fn z_score(&self, value: f32) -> Option<f32> {
+        if self.count < MIN_PROFILE_SAMPLES {
+            return None;
+        }
+        let floor = (self.mean.abs() * 0.05).max(1e-3);
+        Some((value - self.mean).abs() / self.std_dev().max(floor))
+    }
+}
+
+impl InputSample {
+    /// Drops summaries too small to be meaningful and clamps the rest to
+    /// plausible ranges so a crafted client can't skew a profile.
+    pub fn sanitized(mut self) -> Self {
+        if let Some(k) = &mut self.keystrokes {
+            if k.key_count < 5 {
+                self.keystrokes = None;
+            } else {
+                k.mean_dwell_ms = k.mean_dwell_ms.clamp(0.0, 2_000.0);
+                k.mean_flight_ms = k.mean_flight_ms.clamp(0.0, 5_000.0);
+                k.flight_std_ms = k.flight_std_ms.clamp(0.0, 5_000.0);
+            }
+        }
+        if let Some(p) = &mut self.pointer {
+            if p.move_count < 10 {
+                self.pointer = None;
+            } else {
+                p.mean_speed = p.mean_speed.clamp(0.0, 20.0);
+                p.straightness = p.straightness.clamp(0.0, 1.0);
+            }
+        }
+        self
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.keystrokes.is_none() && self.pointer.is_none()
+    }
+}
+
+impl InputProfile {
+    pub fn update(&mut self, sample: &InputSample) {
+        if sample.is_empty() {
+            return;
+        }
+        self.samples += 1;
+        if let Some(k) = &sample.keystrokes {
+            self.dwell.push(k.mean_dwell_ms);
+            self.flight.push(k.mean_flight_ms);
+            self.flight_std.push(k.flight_std_ms);
+        }
+        if let Some(p) = &sample.pointer {
+            self.pointer_speed.push(p.mean_speed);
+            self.straightness.push(p.straightness);
+        }
+    }
+
+    /// Compares a new attempt against the candidate's own history.
+    /// Returns None when there is nothing to compare yet.
+    pub fn compare(&self, sample: &InputSample) -> Option<InputConsistency> {
+        let mut scores = Vec::new();
+        if let Some(k) = &sample.keystrokes {
+            scores.extend(self.dwell.z_score(k.mean_dwell_ms));
+            scores.extend(self.flight.z_score(k.mean_flight_ms));
+            scores.extend(self.flight_std.z_score(k.flight_std_ms));
+        }
+        if let Some(p) = &sample.pointer {
+            scores.extend(self.pointer_speed.z_score(p.mean_speed));
+            scores.extend(self.straightness.z_score(p.straightness));
+        }
+        if scores.is_empty() {
+            return None;
+        }
+
+        let deviation = scores.iter().sum::<f32>() / scores.len() as f32;
+        Some(InputConsistency {
+            deviation,
+            profile_samples: self.samples,
+            impersonation_suspected: deviation > IMPERSONATION_DEVIATION,
+        })
+    }
+}
+
+impl InputConsistency {
+    /// Maps the deviation onto the 0..1 scale used by `consistency_score`.
+    pub fn consistency_score(&self) -> f32 {
+        1.0 / (1.0 + self.deviation / IMPERSONATION_DEVIATION)
+    }
+}
@@ -0,0 +1,126 @@
+use serde::{Deserialize, Serialize};
+use crate::dynamic_questions::DynamicQuestion;
+
+// Item difficulty in 0..1 maps linearly onto the logit scale -3..3
+const DIFFICULTY_SCALE: f64 = 6.0;
+// Standard normal prior keeps all-correct and all-wrong estimates finite
+const PRIOR_SD: f64 = 1.0;
+const MAX_ITERATIONS: usize = 50;
+const TOLERANCE: f64 = 1e-6;
+const THETA_BOUND: f64 = 4.0;
+
+/// Two-parameter logistic item parameters. With `discrimination` fixed at
+/// 1.0 this is the Rasch model.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+pub struct ItemParameters {
+    pub discrimination: f64,
+    pub difficulty: f64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+pub struct AbilityEstimate {
+    pub theta: f64,
+    pub standard_error: f64,
+}
+
+impl ItemParameters {
+    pub fn from_question(question: &DynamicQuestion) -> Self {
+        ItemParameters {
+            discrimination: 1.0,
+            difficulty: logit_difficulty(question.difficulty),
+        }
+    }
+
+    pub fn probability(&self, theta: f64) -> f64 {
+        1.0 / (1.0 + (-self.discrimination * (theta - self.difficulty)).exp())
+    }
+
+    pub fn information(&self, theta: f64) -> f64 {
+        let p = self.probability(theta);
+        self.discrimination.powi(2) * p * (1.0 - p)
+    }
+}
+
+/// Maps a 0..1 item difficulty onto the IRT logit scale.
+pub fn logit_difficulty(difficulty: f32) -> f64 {
+    (difficulty as f64 - 0.5) * DIFFICULTY_SCALE
+}
+
+/// Maps a logit-scale value back onto the 0..1 difficulty used by questions.
+pub fn unit_difficulty(logit: f64) -> f32 {
+    (logit / DIFFICULTY_SCALE + 0.5).clamp(0.0, 1.0) as f32
+}
+
+/// Maximum a posteriori ability estimate by Newton-Raphson. Responses are
+/// scores in 0..1, so partial credit contributes proportionally.
+pub fn estimate_ability(items: &[ItemParameters], responses: &[f32]) -> AbilityEstimate {
+    let mut theta = 0.0;
+    for _ in 0..MAX_ITERATIONS {
+        let mut gradient = -theta / PRIOR_SD.powi(2);
+        let mut hessian = -1.0 / PRIOR_SD.powi(2);
+        for (item, &response) in items.iter().zip(responses) {
+            let p = item.probability(theta);
+            gradient += item.discrimination * (response as f64 - p);
+            hessian -= item.information(theta);
+        }
+
+        let step = gradient / hessian;
+        theta = (theta - step).clamp(-THETA_BOUND, THETA_BOUND);
+        if step.abs() < TOLERANCE {
+            break;
+        }
+    }
+
+    AbilityEstimate {
+        theta,
+        standard_error: standard_error(items, theta),
+    }
+}
+
+pub fn standard_error(items: &[ItemParameters], theta: f64) -> f64 {
+    let information: f64 = items.iter().map(|item| item.information(theta)).sum::<f64>()
+        + 1.0 / PRIOR_SD.powi(2);
+    1.0 / information.sqrt()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn items(count: usize) -> Vec<ItemParameters> {
+        (0..count).map(|_| ItemParameters { discrimination: 1.0, difficulty: 0.0 }).collect()
+    }
+
+    #[test]
+    fn difficulty_scales_round_trip() {
+        assert_eq!(logit_difficulty(0.5), 0.0);
+        assert_eq!(logit_difficulty(1.0), 3.0);
+        assert_eq!(unit_difficulty(logit_difficulty(0.8)), 0.8);
+        assert_eq!(unit_difficulty(10.0), 1.0);
+    }
+
+    #[test]
+    fn prior_keeps_perfect_scores_finite_and_symmetric() {
+        let correct = estimate_ability(&items(5), &[1.0; 5]);
+        let wrong = estimate_ability(&items(5), &[0.0; 5]);
+        assert!(correct.theta > 0.5 && correct.theta < THETA_BOUND, "{:?}", correct);
+        assert!((correct.theta + wrong.theta).abs() < 1e-9);
+        assert!((correct.standard_error - wrong.standard_error).abs() < 1e-9);
+
+        let half = estimate_ability(&items(5), &[0.5; 5]);
+        assert!(half.theta.abs() < 1e-9, "partial credit counts proportionally");
+    }
+
+    #[test]
+    fn more_items_narrow_the_estimate() {
+        let short = estimate_ability(&items(3), &[1.0, 0.0, 1.0]);
+        let long = estimate_ability(&items(12), &[1.0, 0.0, 1.0].repeat(4));
+        assert!(long.standard_error < short.standard_error);
+        assert!(long.theta > short.theta, "the prior weighs less as evidence grows");
+
+        // Getting hard items right says more than getting easy ones right
+        let hard = [ItemParameters { discrimination: 1.0, difficulty: 2.0 }; 3];
+        let easy = [ItemParameters { discrimination: 1.0, difficulty: -2.0 }; 3];
+        assert!(estimate_ability(&hard, &[1.0; 3]).theta > estimate_ability(&easy, &[1.0; 3]).theta);
+    }
+}
@@ -0,0 +1,318 @@
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use crate::dynamic_questions::DynamicQuestion;
+
+// Templates need this many responses before calibration touches them
+pub const MIN_CALIBRATION_RESPONSES: u64 = 30;
+// Generated stems are mostly unique, so per-item tracking is capped
+const MAX_TRACKED_ITEMS: usize = 10_000;
+const MIN_EXPECTED_TIME: u32 = 5;
+const MAX_EXPECTED_TIME: u32 = 300;
+
+/// One answered item from a completed evaluation.
+#[derive(Debug, Clone)]
+pub struct ItemResponse<'a> {
+    pub question: &'a DynamicQuestion,
+    pub credit: f32,
+    pub answer_time: Option<u32>,    // on the standard timescale
+    pub selected_option: Option<u8>, // the decrypted answer, if the backend could decrypt it
+}
+
+/// Running sums for classical test theory statistics.
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct ResponseAccumulator {
+    pub responses: u64,
+    pub sum_correct: f64,
+    pub sum_correct_sq: f64,
+    pub sum_rest: f64,
+    pub sum_rest_sq: f64,
+    pub sum_correct_rest: f64,
+    pub timed_responses: u64,
+    pub sum_time: f64,
+    #[serde(default)]
+    pub sum_difficulty: f64, // generator difficulty, before any calibration offset
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct ResponseStats {
+    pub responses: u64,
+    pub p_value: f32,                 // share answered correctly
+    pub point_biserial: Option<f32>,  // correlation with the rest of the test
+    pub mean_time: Option<f32>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct ItemRecord {
+    pub template: String,
+    pub accumulator: ResponseAccumulator,
+    pub option_selections: BTreeMap<String, u64>, // option text -> times chosen
+    pub correct_option: String,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct ItemReport {
+    pub item: String,
+    pub template: String,
+    pub stats: ResponseStats,
+    pub distractor_rates: BTreeMap<String, f32>, // share of selections per wrong option
+}
+
+/// Shifts a template's generated difficulty toward what candidates found,
+/// keeping each question's place on the session's difficulty ramp.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+pub struct TemplateCalibration {
+    pub difficulty_offset: f32, // observed minus generated difficulty
+    pub expected_time: u32,
+    pub version: u32,           // 1 for the template's first calibration, bumped by each one after
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct TemplateReport {
+    pub template: String,
+    pub stats: ResponseStats,
+    pub calibration: Option<TemplateCalibration>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ItemStatsReport {
+    pub templates: Vec<TemplateReport>,
+    pub items: Vec<ItemReport>,
+}
+
+#[derive(Debug, Default)]
+pub struct ItemStatistics {
+    templates: HashMap<String, ResponseAccumulator>,
+    items: HashMap<String, ItemRecord>,
+    calibrations: HashMap<String, TemplateCalibration>,
+    offset_history: HashMap<String, Vec<f32>>, // every offset per template, by version
+}
+
+/// Generator template a question came from, e.g. "math:addition". Templates
+/// without a type parameter are keyed by their question text.
+pub fn template_key(quiz_type: &str, question: &DynamicQuestion) -> String {
+    let kind = question.parameters.get("type").unwrap_or(&question.question_text);
+    format!("{}:{}", quiz_type, kind)
+}
+
+impl ResponseAccumulator {
+    fn push(&mut self, correct: f64, rest: f64, time: Option<u32>, difficulty: f64) {
+        self.responses += 1;
+        self.sum_difficulty += difficulty;
+        self.sum_correct += correct;
+        self.sum_correct_sq += correct * correct;
+        self.sum_rest += rest;
+        self.sum_rest_sq += rest * rest;
+        self.sum_correct_rest += correct * rest;
+        if let Some(time) = time {
+            self.timed_responses += 1;
+            self.sum_time += time as f64;
+        }
+    }
+
+    pub fn stats(&self) -> ResponseStats {
+        let n = self.responses as f64;
+        let p_value = if n > 0.0 { self.sum_correct / n } else { 0.0 };
+
+        // Pearson correlation between item score and rest score, which is the
+        // point-biserial coefficient when item scores are 0 or 1
+        let point_biserial = {
+            let cov = n * self.sum_correct_rest - self.sum_correct * self.sum_rest;
+            let var_item = n * self.sum_correct_sq - self.sum_correct.powi(2);
+            let var_rest = n * self.sum_rest_sq - self.sum_rest.powi(2);
+            (n >= 2.0 && var_item > 0.0 && var_rest > 0.0)
+                .then(|| (cov / (var_item * var_rest).sqrt()) as f32)
+        };
+
+        ResponseStats {
+            responses: self.responses,
+            p_value: p_value as f32,
+            point_biserial,
+            mean_time: (self.timed_responses > 0)
+                .then(|| (self.sum_time / self.timed_responses as f64) as f32),
+        }
+    }
+}
+
+impl ItemStatistics {
+    /// Adds one completed evaluation. Each item is correlated with the
+    /// candidate's score on the other items in the same session.
+    pub fn record(&mut self, quiz_type: &str, responses: &[ItemResponse]) {
+        if responses.len() < 2 {
+            return;
+        }
+        let total: f64 = responses.iter().map(|r| r.credit as f64).sum();
+        let others = (responses.len() - 1) as f64;
+
+        for response in responses {
+            let question = response.question;
+            let correct = response.credit as f64;
+            let rest = (total - correct) / others;
+            let template = template_key(quiz_type, question);
+            // Served difficulties include the offset in force when the question
+            // was served, which a recalibration since may have replaced; strip
+            // that one so every calibration compares against the generator
+            let offset = question.calibration_version
+                .and_then(|version| self.offset_history.get(&template)?.get(version.checked_sub(1)? as usize).copied())
+                .unwrap_or(0.0);
+            let difficulty = (question.difficulty - offset) as f64;
+
+            self.templates.entry(template.clone()).or_default()
+                .push(correct, rest, response.answer_time, difficulty);
+
+            if self.items.len() >= MAX_TRACKED_ITEMS && !self.items.contains_key(&question.question_text) {
+                continue;
+            }
+            let record = self.items.entry(question.question_text.clone()).or_insert_with(|| ItemRecord {
+                template,
+                correct_option: question.options.get(question.correct_answer as usize).cloned().unwrap_or_default(),
+                ..Default::default()
+            });
+            record.accumulator.push(correct, rest, response.answer_time, difficulty);
+            if let Some(option) = response.selected_option.and_then(|i| question.options.get(i as usize)) {
+                *record.option_selections.entry(option.clone()).or_default() += 1;
+            }
+        }
+    }
+
+    /// Re-derives difficulty and expected time for every template with
+    /// enough responses. Returns the number of templates updated.
+    pub fn calibrate(&mut self) -> usize {
+        let mut updated = 0;
+        for (template, accumulator) in &self.templates {
+            if accumulator.responses < MIN_CALIBRATION_RESPONSES {
+                continue;
+            }
+            let stats = accumulator.stats();
+            let Some(mean_time) = stats.mean_time else { continue };
+
+            let observed = (1.0 - stats.p_value).clamp(0.05, 0.95);
+            let generated = (accumulator.sum_difficulty / accumulator.responses as f64) as f32;
+            let history = self.offset_history.entry(template.clone()).or_default();
+            history.push(observed - generated);
+            self.calibrations.insert(template.clone(), TemplateCalibration {
+                difficulty_offset: observed - generated,
+                expected_time: (mean_time.round() as u32).clamp(MIN_EXPECTED_TIME, MAX_EXPECTED_TIME),
+                version: history.len() as u32,
+            });
+            updated += 1;
+        }
+        updated
+    }
+
+    /// Applies calibrated values to a freshly generated question, if its
+    /// template has any. Difficulty is shifted, not replaced.
+    pub fn apply_calibration(&self, quiz_type: &str, question: &mut DynamicQuestion) {
+        if let Some(calibration) = self.calibrations.get(&template_key(quiz_type, question)) {
+            question.difficulty = (question.difficulty + calibration.difficulty_offset).clamp(0.05, 0.95);
+            question.expected_time = calibration.expected_time;
+            question.calibration_version = Some(calibration.version);
+        }
+    }
+
+    pub fn report(&self) -> ItemStatsReport {
+        let mut templates: Vec<TemplateReport> = self.templates.iter()
+            .map(|(template, accumulator)| TemplateReport {
+                template: template.clone(),
+                stats: accumulator.stats(),
+                calibration: self.calibrations.get(template).copied(),
+            })
+            .collect();
+        templates.sort_by(|a, b| a.template.cmp(&b.template));
+
+        let mut items: Vec<ItemReport> = self.items.iter()
+            .map(|(item, record)| {
+                let selections: u64 = record.option_selections.values().sum();
+                let distractor_rates = record.option_selections.iter()
+                    .filter(|(option, _)| **option != record.correct_option)
+                    .map(|(option, &count)| (option.clone(), count as f32 / selections as f32))
+                    .collect();
+                ItemReport {
+                    item: item.clone(),
+                    template: record.template.clone(),
+                    stats: record.accumulator.stats(),
+                    distractor_rates,
+                }
+            })
+            .collect();
+        items.sort_by(|a, b| a.item.cmp(&b.item));
+
+        ItemStatsReport { templates, items }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn question(difficulty: f32) -> DynamicQuestion {
+        DynamicQuestion {
+            question_id: "q".to_string(),
+            question_text: format!("What is 1 + {}?", rand::random::<u32>()),
+            options: vec!["2".to_string(), "3".to_string()],
+            correct_answer: 0,
+            part_answers: Vec::new(),
+            parameters: HashMap::from([("type".to_string(), "addition".to_string())]),
+            difficulty,
+            expected_time: 30,
+            calibration_version: None,
+        }
+    }
+
+    fn served(stats: &ItemStatistics) -> DynamicQuestion {
+        let mut question = question(0.5);
+        stats.apply_calibration("math", &mut question);
+        question
+    }
+
+    fn record(stats: &mut ItemStatistics, questions: &[DynamicQuestion], credit: f32, selected: u8) {
+        let responses: Vec<ItemResponse> = questions.iter()
+            .map(|question| ItemResponse { question, credit, answer_time: Some(20), selected_option: Some(selected) })
+            .collect();
+        stats.record("math", &responses);
+    }
+
+    fn mean_generated_difficulty(stats: &ItemStatistics) -> f64 {
+        let accumulator = &stats.templates["math:addition"];
+        accumulator.sum_difficulty / accumulator.responses as f64
+    }
+
+    #[test]
+    fn recalibration_strips_the_offset_a_question_was_served_with() {
+        let mut stats = ItemStatistics::default();
+        for _ in 0..MIN_CALIBRATION_RESPONSES {
+            let pair = [served(&stats), served(&stats)];
+            record(&mut stats, &pair, 1.0, 0);
+        }
+        assert_eq!(stats.calibrate(), 1);
+        let first = served(&stats);
+        assert_eq!(first.calibration_version, Some(1));
+        assert!((first.difficulty - 0.05).abs() < 1e-6);
+
+        for _ in 0..MIN_CALIBRATION_RESPONSES {
+            let pair = [served(&stats), served(&stats)];
+            record(&mut stats, &pair, 0.0, 1);
+        }
+        stats.calibrate();
+        assert_eq!(stats.calibrations["math:addition"].version, 2);
+
+        // Answered after the recalibration, but served under version 1
+        record(&mut stats, &[first.clone(), first], 1.0, 0);
+        assert!((mean_generated_difficulty(&stats) - 0.5).abs() < 1e-6);
+    }
+
+    #[test]
+    fn distractor_rates_count_wrong_selections() {
+        let mut stats = ItemStatistics::default();
+        let questions = [question(0.5), question(0.5)];
+        record(&mut stats, &questions, 1.0, 0);
+        record(&mut stats, &questions, 0.0, 1);
+        record(&mut stats, &questions, 0.0, 1);
+
+        let report = stats.report();
+        assert_eq!(report.items.len(), 2);
+        let item = &report.items[0];
+        assert_eq!(item.stats.responses, 3);
+        assert!((item.distractor_rates["3"] - 2.0 / 3.0).abs() < 1e-6);
+        assert!(!item.distractor_rates.contains_key("2"));
+    }
+}
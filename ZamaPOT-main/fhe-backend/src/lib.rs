@@ -0,0 +1,27 @@
+pub mod quiz_types;
+pub mod dynamic_questions;
+pub mod telemetry;
+pub mod input_dynamics;
+pub mod consistency;
+pub mod answer_patterns;
+pub mod behavior;
+pub mod cheating_model;
+pub mod shadow;
+pub mod accommodations;
+pub mod scoring;
+pub mod irt;
+pub mod adaptive;
+pub mod item_stats;
+pub mod rating;
+pub mod ciphertext;
+pub mod client_keys;
+pub mod certificates;
+pub mod disclosure;
+pub mod registry;
+pub mod attestation;
+pub mod rpc;
+pub mod relayer;
+pub mod indexer;
+pub mod credentials;
+pub mod open_badges;
+pub mod rendering;
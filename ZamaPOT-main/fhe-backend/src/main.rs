@@ -1,28 +1,46 @@
-mod quiz_types;
-mod dynamic_questions;
-
-use actix_web::{web, App, HttpServer, HttpResponse, Result};
+use actix_web::{web, App, HttpRequest, HttpServer, HttpResponse, Result};
 use serde::{Deserialize, Serialize};
-use rand::Rng;
-use std::collections::HashMap;
+use rand::{Rng, RngCore};
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::sync::{Arc, Mutex};
-use quiz_types::QuizConfig;
-use dynamic_questions::{DynamicQuestion, UserSession};
+use fhe_backend::quiz_types::QuizConfig;
+use fhe_backend::dynamic_questions::{DynamicQuestion, UserSession};
+use fhe_backend::telemetry::{self, TelemetrySummary};
+use fhe_backend::input_dynamics::{InputConsistency, InputProfile};
+use fhe_backend::consistency::{AttemptHistory, AttemptRecord, ConsistencyReport};
+use fhe_backend::answer_patterns::AnswerPattern;
+use fhe_backend::behavior::BehaviorData;
+use fhe_backend::cheating_model::CheatingModel;
+use fhe_backend::shadow::ShadowEvaluator;
+use fhe_backend::accommodations::{Accommodation, AccommodationAdjustment};
+use fhe_backend::scoring::{self, ItemResult, ScoringPolicy};
+use fhe_backend::irt::{self, AbilityEstimate, ItemParameters};
+use fhe_backend::adaptive::AdaptiveConfig;
+use fhe_backend::item_stats::{ItemResponse, ItemStatistics};
+use fhe_backend::rating::{RatedItem, SkillRating};
+use fhe_backend::ciphertext::{self, Ciphertext, CiphertextPolicy, Envelope};
+use fhe_backend::client_keys::{self, ClientKey, KeyError, KeyInfo, KeyLimits, KeyRegistration, KeyStore};
+use fhe_backend::certificates::{self, CertificatePayload, IssuerKey, SignedCertificate};
+use fhe_backend::disclosure::{self, DisclosureError, Presentation, PresentationRequest};
+use fhe_backend::registry::{CertificateRegistry, RevocationError};
+use fhe_backend::attestation::{self, MintArgs, MintAttestation, MintDomain, OracleKey};
+use fhe_backend::relayer::{self, RelayStatus, Relayer, RelayerConfig};
+use fhe_backend::indexer::{ChainIndexer, IndexerConfig};
+use fhe_backend::credentials;
+use fhe_backend::open_badges::{self, AchievementCredential};
+use fhe_backend::rendering;
+use fhe_backend::registry::CertificateStatus;
 
 #[derive(Debug, Deserialize)]
 struct QuizRequest {
     encrypted_answers: Vec<String>,
     user_id: String,
+    #[allow(dead_code)] // the session determines the quiz type
     quiz_type: String,
     behavior_data: BehaviorData,
-}
-
-#[derive(Debug, Deserialize)]
-struct BehaviorData {
-    answer_times: Vec<u32>, // Time spent on each question in seconds
-    switch_counts: Vec<u32>, // How many times each answer was changed
-    start_time: u64,
-    end_time: u64,
+    #[serde(default)]
+    wallet_address: Option<String>, // relayed badges are transferred here
 }
 
 #[derive(Debug, Serialize)]
@@ -31,12 +49,22 @@ struct QuizResponse {
     encrypted_score: String,
     level: u8,
     correct_answers: usize,
+    raw_score: f32,
+    weighted_score: f32,
+    ability: AbilityEstimate,
+    skill_rating: Option<SkillRating>,
     total_questions: usize,
     quiz_type: String,
     certificate_id: String,
+    certificate: SignedCertificate,
+    holder_secret: String, // shown once; authorizes selective-disclosure presentations
+    mint: Option<MintAttestation>, // only for passed attempts
+    relay: Option<RelayStatus>,
     cheating_likelihood: f32,
     behavior_analysis: BehaviorAnalysis,
     is_flagged: bool,
+    min_time_violation: bool,
+    accommodation: Option<AccommodationAdjustment>,
 }
 
 #[derive(Debug, Serialize)]
@@ -45,46 +73,177 @@ struct BehaviorAnalysis {
     time_consistency: f32,
     switch_frequency: f32,
     pattern_deviation: f32,
+    telemetry: TelemetrySummary,
+    input_consistency: Option<InputConsistency>,
+    attempt_consistency: Option<ConsistencyReport>,
+    consistency_score: f32,
+    answer_pattern: Option<AnswerPattern>,
 }
 
 #[derive(Debug, Deserialize)]
 struct SessionRequest {
     user_id: String,
     quiz_type: String,
+    #[serde(default)]
+    adaptive: bool, // serve items one at a time via /next-question
+    #[serde(default)]
+    key_id: Option<String>, // registered client key the answers will be encrypted under
+}
+
+#[derive(Debug, Deserialize)]
+struct BindKeyRequest {
+    key_id: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct NextQuestionRequest {
+    session_id: String,
+    encrypted_answer: String, // answer to the most recently served question
+}
+
+#[derive(Debug, Serialize)]
+struct NextQuestionResponse {
+    session_id: String,
+    finished: bool,
+    items_answered: usize,
+    ability: AbilityEstimate,
+    question: Option<DynamicQuestion>,
+}
+
+#[derive(Debug, Deserialize)]
+struct AccommodationRequest {
+    user_id: String,
+    #[serde(flatten)]
+    accommodation: Accommodation,
+}
+
+#[derive(Debug, Serialize)]
+struct CategoryRating {
+    quiz_type: String,
+    rating: f64,
+    deviation: f64,
+    attempts: u32,
+    last_updated: u64,
+    matched_difficulty: f32, // where new sessions start
+}
+
+#[derive(Debug, Serialize)]
+struct SkillProfile {
+    user_id: String,
+    ratings: Vec<CategoryRating>,
 }
 
 #[derive(Debug, Serialize)]
 struct SessionResponse {
     session_id: String,
+    adaptive: bool,
     questions: Vec<DynamicQuestion>,
 }
 
 struct MobileFHE {
     quizzes: HashMap<String, QuizConfig>,
     user_sessions: Arc<Mutex<HashMap<String, UserSession>>>,
+    evaluated_sessions: Arc<Mutex<HashSet<String>>>, // consumed, so never evaluated twice
+    input_profiles: Arc<Mutex<HashMap<String, InputProfile>>>,
+    attempt_histories: Arc<Mutex<HashMap<(String, String), AttemptHistory>>>,
+    accommodations: Arc<Mutex<HashMap<String, Accommodation>>>,
+    item_stats: Arc<Mutex<ItemStatistics>>,
+    skill_ratings: Arc<Mutex<HashMap<String, HashMap<String, SkillRating>>>>, // user -> quiz type -> rating
+    cheating_model: CheatingModel,
+    shadow: ShadowEvaluator,
+    issuer_key: IssuerKey,
+    certificate_registry: Arc<CertificateRegistry>,
+    oracle_key: OracleKey,
+    relayer: Option<Relayer>,
+    mint_commitments: Arc<Mutex<HashSet<[u8; 32]>>>, // bytes32 scores already attested
+    client_keys: KeyStore,
 }
 
 impl MobileFHE {
-    fn new() -> Self {
+    #[allow(clippy::too_many_arguments)]
+    fn new(
+        cheating_model: CheatingModel,
+        shadow: ShadowEvaluator,
+        issuer_key: IssuerKey,
+        certificate_registry: Arc<CertificateRegistry>,
+        oracle_key: OracleKey,
+        relayer: Option<Relayer>,
+        mut policy_overrides: HashMap<String, ScoringPolicy>,
+        key_limits: KeyLimits,
+    ) -> Self {
         let mut quizzes = HashMap::new();
-        quizzes.insert("programming".to_string(), QuizConfig::programming_quiz());
-        quizzes.insert("math".to_string(), QuizConfig::math_quiz());
-        quizzes.insert("blockchain".to_string(), QuizConfig::blockchain_quiz());
-        quizzes.insert("security".to_string(), QuizConfig::security_quiz());
+        for mut config in [
+            QuizConfig::programming_quiz(),
+            QuizConfig::math_quiz(),
+            QuizConfig::blockchain_quiz(),
+            QuizConfig::security_quiz(),
+        ] {
+            if let Some(policy) = policy_overrides.remove(&config.quiz_type) {
+                match policy.validate() {
+                    Ok(()) => config.scoring = policy,
+                    Err(e) => println!("⚠️  Ignoring scoring policy for '{}': {}", config.quiz_type, e),
+                }
+            }
+            match config.lint() {
+                Ok(()) => {
+                    quizzes.insert(config.quiz_type.clone(), config);
+                }
+                Err(e) => println!("⚠️  Rejected quiz bank '{}': {}", config.quiz_type, e),
+            }
+        }
+        for quiz_type in policy_overrides.keys() {
+            println!("⚠️  Ignoring scoring policy for unknown quiz '{}'", quiz_type);
+        }
         
         MobileFHE { 
             quizzes,
             user_sessions: Arc::new(Mutex::new(HashMap::new())),
+            evaluated_sessions: Arc::new(Mutex::new(HashSet::new())),
+            input_profiles: Arc::new(Mutex::new(HashMap::new())),
+            attempt_histories: Arc::new(Mutex::new(HashMap::new())),
+            accommodations: Arc::new(Mutex::new(HashMap::new())),
+            item_stats: Arc::new(Mutex::new(ItemStatistics::default())),
+            skill_ratings: Arc::new(Mutex::new(HashMap::new())),
+            cheating_model,
+            shadow,
+            issuer_key,
+            certificate_registry,
+            oracle_key,
+            relayer,
+            mint_commitments: Arc::new(Mutex::new(HashSet::new())),
+            client_keys: KeyStore::new(key_limits),
         }
     }
 
-    fn create_user_session(&self, user_id: String, quiz_type: String) -> String {
+    fn create_user_session(&self, user_id: String, quiz_type: String, adaptive: bool) -> String {
         let session_id = format!("{}_{}_{}", user_id, quiz_type, rand::thread_rng().gen::<u32>());
         let question_count = self.quizzes.get(&quiz_type)
             .map(|config| config.questions.len())
             .unwrap_or(3);
         
-        let user_session = UserSession::new(user_id.clone(), &quiz_type, question_count);
+        let accommodation = self.accommodations.lock().unwrap()
+            .get(&user_id)
+            .cloned()
+            .unwrap_or_default();
+        // Start where the candidate's rating says they have even odds
+        let matched_difficulty = self.skill_ratings.lock().unwrap()
+            .get(&user_id)
+            .and_then(|ratings| ratings.get(&quiz_type))
+            .map(|rating| rating.matched_difficulty())
+            .unwrap_or(0.5);
+        let mut user_session = if adaptive {
+            UserSession::new_adaptive(user_id.clone(), &quiz_type, AdaptiveConfig::default(), matched_difficulty)
+        } else {
+            // The fixed ramp climbs 0.2 per item, starting just below the matched level
+            let starting_difficulty = (matched_difficulty - 0.2).clamp(0.05, 0.95);
+            UserSession::with_starting_difficulty(user_id.clone(), &quiz_type, question_count, starting_difficulty)
+        };
+        let item_stats = self.item_stats.lock().unwrap();
+        for question in &mut user_session.questions {
+            item_stats.apply_calibration(&quiz_type, question);
+        }
+        drop(item_stats);
+        let user_session = user_session.with_accommodation(accommodation);
         
         let mut sessions = self.user_sessions.lock().unwrap();
         sessions.insert(session_id.clone(), user_session);
@@ -92,10 +251,146 @@ impl MobileFHE {
         session_id
     }
 
+    /// Binds a session to one of its user's live client keys.
+    fn bind_session_key(&self, session_id: &str, key_id: &str) -> Result<KeyInfo, KeyError> {
+        let mut sessions = self.user_sessions.lock().unwrap();
+        let user_session = sessions.get_mut(session_id)
+            .ok_or_else(|| KeyError::UnknownSession(session_id.to_string()))?;
+        let key = self.client_keys.owned_by(key_id, &user_session.user_id, now_secs())?;
+        user_session.key_id = Some(key.key_id.clone());
+        Ok(key.info())
+    }
+
+    /// Checks that every enveloped answer names a key this session may use:
+    /// the bound key if there is one, and otherwise a live key of the user.
+    fn check_answer_keys(&self, session_id: &str, encrypted_answers: &[String]) -> Result<(), KeyError> {
+        let (user_id, bound_key) = {
+            let sessions = self.user_sessions.lock().unwrap();
+            let Some(user_session) = sessions.get(session_id) else { return Ok(()) };
+            (user_session.user_id.clone(), user_session.key_id.clone())
+        };
+        let now = now_secs();
+
+        let envelopes = encrypted_answers.iter()
+            .flat_map(|answer| answer.split(ciphertext::PART_SEPARATOR))
+            .filter_map(|part| match Ciphertext::parse(part) {
+                Ok(Ciphertext::Envelope(envelope)) => Some(envelope),
+                _ => None,
+            });
+        for envelope in envelopes {
+            if let Some(bound_key) = &bound_key {
+                if &envelope.key_id != bound_key {
+                    return Err(KeyError::UnboundKey { session: bound_key.clone(), ciphertext: envelope.key_id });
+                }
+            }
+            // Every scheme is keyed, so the key must be the user's own
+            let key = self.client_keys.owned_by(&envelope.key_id, &user_id, now)?;
+            if key.scheme != envelope.scheme {
+                return Err(KeyError::SchemeMismatch { key: key.scheme, ciphertext: envelope.scheme });
+            }
+        }
+        Ok(())
+    }
+
+    /// Checks that every non-empty answer has one part per part of its item.
+    /// For adaptive sessions the answers start at the current item.
+    fn check_part_counts(&self, session_id: &str, encrypted_answers: &[String]) -> Result<(), (usize, String)> {
+        let sessions = self.user_sessions.lock().unwrap();
+        let Some(user_session) = sessions.get(session_id) else { return Ok(()) };
+        let first = user_session.adaptive.as_ref().map_or(0, |state| state.responses.len());
+        for (index, answer) in encrypted_answers.iter().enumerate().map(|(i, answer)| (first + i, answer)) {
+            let Some(question) = user_session.questions.get(index) else { break };
+            let (expected, parts) = (question.answer_key().len(), answer.split(ciphertext::PART_SEPARATOR).count());
+            if !answer.is_empty() && parts != expected {
+                return Err((index, format!("item has {} part(s), the answer has {}", expected, parts)));
+            }
+        }
+        Ok(())
+    }
+
+    /// Scores the answer to the current adaptive item and serves the next one,
+    /// unless the ability estimate is precise enough or the item cap is reached.
+    fn next_question(&self, session_id: &str, encrypted_answer: String) -> Option<NextQuestionResponse> {
+        let mut sessions = self.user_sessions.lock().unwrap();
+        let user_session = sessions.get_mut(session_id)?;
+        let state = user_session.adaptive.as_ref()?;
+        if state.finished {
+            return None;
+        }
+
+        let index = state.responses.len();
+        let credit = self.simulate_encrypted_credit(user_session, &encrypted_answer, index, &user_session.questions[index]);
+        let items: Vec<ItemParameters> = user_session.questions.iter()
+            .map(ItemParameters::from_question)
+            .collect();
+        let state = user_session.adaptive.as_mut()?;
+        state.record(&items, encrypted_answer, credit);
+        let (finished, items_answered, ability) = (state.finished, state.responses.len(), state.estimate);
+
+        let question = user_session.serve_next_question(&self.item_stats.lock().unwrap()).cloned();
+        Some(NextQuestionResponse {
+            session_id: session_id.to_string(),
+            finished,
+            items_answered,
+            ability,
+            question,
+        })
+    }
+
+    fn get_skill_profile(&self, user_id: &str) -> SkillProfile {
+        let now = now_secs();
+        let mut ratings: Vec<CategoryRating> = self.skill_ratings.lock().unwrap()
+            .get(user_id)
+            .map(|ratings| ratings.iter()
+                .map(|(quiz_type, rating)| CategoryRating {
+                    quiz_type: quiz_type.clone(),
+                    rating: rating.rating,
+                    deviation: rating.current_deviation(now),
+                    attempts: rating.attempts,
+                    last_updated: rating.last_updated,
+                    matched_difficulty: rating.matched_difficulty(),
+                })
+                .collect())
+            .unwrap_or_default();
+        ratings.sort_by(|a, b| a.quiz_type.cmp(&b.quiz_type));
+
+        SkillProfile {
+            user_id: user_id.to_string(),
+            ratings,
+        }
+    }
+
+    fn set_accommodation(&self, user_id: String, accommodation: Accommodation) -> Accommodation {
+        let accommodation = accommodation.sanitized();
+        let mut accommodations = self.accommodations.lock().unwrap();
+        if accommodation.is_standard() {
+            accommodations.remove(&user_id);
+        } else {
+            accommodations.insert(user_id, accommodation.clone());
+        }
+        accommodation
+    }
+
+    fn get_accommodation(&self, user_id: &str) -> Accommodation {
+        self.accommodations.lock().unwrap().get(user_id).cloned().unwrap_or_default()
+    }
+
+    fn is_evaluated(&self, session_id: &str) -> bool {
+        self.evaluated_sessions.lock().unwrap().contains(session_id)
+    }
+
+    /// An adaptive session still short of its stopping rule; scoring it now
+    /// would grade only the first few items.
+    fn is_unfinished_adaptive(&self, session_id: &str) -> bool {
+        self.user_sessions.lock().unwrap().get(session_id)
+            .and_then(|session| session.adaptive.as_ref())
+            .is_some_and(|state| !state.finished)
+    }
+
     fn get_session_questions(&self, session_id: &str) -> Option<Vec<DynamicQuestion>> {
         let sessions = self.user_sessions.lock().unwrap();
         sessions.get(session_id).map(|session| {
-            session.questions.iter().cloned().collect()
+            session.questions.to_vec()
         })
     }
 
@@ -103,74 +398,209 @@ impl MobileFHE {
         &self, 
         session_id: &str,
         encrypted_answers: &[String], 
-        behavior_data: &BehaviorData
+        behavior_data: &BehaviorData,
+        wallet_address: Option<&str>,
     ) -> Option<QuizResponse> {
         let mut sessions = self.user_sessions.lock().unwrap();
         let user_session = sessions.get_mut(session_id)?;
+        let behavior_data = &behavior_data.clone().sanitized();
         
         // Update behavior metrics
-        for &time in &behavior_data.answer_times {
-            user_session.add_answer_time(time);
-        }
-        for &switches in &behavior_data.switch_counts {
-            if switches > 0 {
-                user_session.increment_switch_count();
-            }
+        // Decryptable answers feed the pattern check even without client events
+        let submitted: Vec<Option<u8>> = match &user_session.adaptive {
+            Some(state) => state.encrypted_answers.iter().map(|answer| self.decrypt_answer(user_session, answer)).collect(),
+            None => encrypted_answers.iter().map(|answer| self.decrypt_answer(user_session, answer)).collect(),
+        };
+        let observed = behavior_data.observe(user_session.questions.len(), &submitted);
+        if observed.events.truncated {
+            println!("⚠️  Telemetry stream truncated to {} events", telemetry::MAX_EVENTS);
         }
+        user_session.record_behavior(behavior_data, &observed);
 
+        // Adaptive sessions were answered item by item and are only evaluated
+        // once finished, so every answered item counts and nothing else does
+        let adaptive_answers = user_session.adaptive.as_ref().map(|state| state.encrypted_answers.clone());
+        if let Some(answers) = &adaptive_answers {
+            user_session.questions.truncate(answers.len().max(1));
+        }
+        let encrypted_answers = adaptive_answers.as_deref().unwrap_or(encrypted_answers);
         let total_questions = user_session.questions.len();
-        let mut correct_count = 0;
 
         // Simulate FHE evaluation of answers
-        for (i, enc_answer) in encrypted_answers.iter().enumerate() {
-            if i < total_questions {
-                if self.simulate_encrypted_check(enc_answer, i, &user_session.questions[i]) {
-                    correct_count += 1;
+        let items: Vec<ItemResult> = user_session.questions.iter().enumerate()
+            .map(|(i, question)| {
+                let enc_answer = encrypted_answers.get(i).map(String::as_str).unwrap_or("");
+                ItemResult {
+                    credit: self.simulate_encrypted_credit(user_session, enc_answer, i, question),
+                    answered: !enc_answer.is_empty(),
+                    difficulty: question.difficulty,
                 }
-            }
-        }
-
-        let score_percentage = correct_count as f32 / total_questions as f32;
+            })
+            .collect();
+        let answers_correct: Vec<bool> = items.iter().map(|item| item.credit >= 1.0).collect();
+        let correct_count = answers_correct.iter().filter(|&&correct| correct).count();
         
-        // Get quiz type from first question parameters
-        let quiz_type = user_session.questions[0].parameters.get("type")
-            .cloned()
-            .unwrap_or_else(|| "math".to_string());
-            
+        let quiz_type = user_session.quiz_type.clone();
         let quiz_config = self.quizzes.get(&quiz_type).or_else(|| self.quizzes.get("math"))?;
-        let passed = score_percentage >= quiz_config.passing_score;
+        let policy = &quiz_config.scoring;
+        let score = policy.score(&items);
+
+        // Ability on a common scale, so sessions of different difficulty compare
+        let item_parameters: Vec<ItemParameters> = user_session.questions.iter()
+            .map(ItemParameters::from_question)
+            .collect();
+        let credits: Vec<f32> = items.iter().map(|item| item.credit).collect();
+        let ability = irt::estimate_ability(&item_parameters, &credits);
+
+        let mut behavior_analysis = self.analyze_behavior(behavior_data, total_questions, observed.telemetry);
+        user_session.record_timing_profile(behavior_analysis.time_consistency, behavior_analysis.pattern_deviation);
+
+        // Compare this attempt with the candidate's own history
+        let input_sample = behavior_data.input.clone().sanitized();
+        let input_consistency = self.input_profiles.lock().unwrap()
+            .get(&user_session.user_id)
+            .and_then(|profile| profile.compare(&input_sample));
+
+        let attempt = AttemptRecord::new(
+            &user_session.questions,
+            &behavior_data.answer_times,
+            &answers_correct,
+            behavior_analysis.time_consistency,
+            behavior_analysis.pattern_deviation,
+        );
+        let history_key = (user_session.user_id.clone(), quiz_type.clone());
+        let attempt_consistency = attempt.as_ref().and_then(|attempt| {
+            self.attempt_histories.lock().unwrap()
+                .get(&history_key)
+                .and_then(|history| history.compare(attempt))
+        });
+
+        let consistency_score = input_consistency.as_ref().map(|c| c.consistency_score())
+            .into_iter()
+            .chain(attempt_consistency.as_ref().map(|c| c.score))
+            .fold(1.0, f32::min);
+        user_session.record_consistency(consistency_score);
         
-        let cheating_likelihood = user_session.calculate_cheating_likelihood();
-        let is_flagged = cheating_likelihood > 0.6; // Flag if cheating likelihood > 60%
+        let signals = user_session.detector_signals();
+        let cheating_likelihood = self.cheating_model.likelihood(&signals);
+        let is_flagged = policy.is_flagged(cheating_likelihood, &self.cheating_model);
+        // Candidate models are scored and logged only; they never change the result
+        self.shadow.observe(session_id, &signals, cheating_likelihood, is_flagged);
+
+        // Only clean attempts extend the history, so an impersonator can't train it
+        let mut skill_rating = None;
+        if !is_flagged {
+            // Distractor rates come from what was submitted, not what the client reports
+            let responses: Vec<ItemResponse> = user_session.questions.iter().enumerate()
+                .map(|(i, question)| ItemResponse {
+                    question,
+                    credit: items[i].credit,
+                    answer_time: behavior_data.answer_times.get(i)
+                        .map(|&t| user_session.accommodation.normalize_time(t)),
+                    selected_option: submitted.get(i).copied().flatten(),
+                })
+                .collect();
+            self.item_stats.lock().unwrap().record(&quiz_type, &responses);
+
+            let rated_items: Vec<RatedItem> = items.iter()
+                .map(|item| RatedItem { difficulty: item.difficulty, score: item.credit })
+                .collect();
+            let mut skill_ratings = self.skill_ratings.lock().unwrap();
+            let rating = skill_ratings.entry(user_session.user_id.clone()).or_default()
+                .entry(quiz_type.clone()).or_default();
+            rating.update(&rated_items, now_secs());
+            skill_rating = Some(*rating);
+
+            self.input_profiles.lock().unwrap()
+                .entry(user_session.user_id.clone())
+                .or_default()
+                .update(&input_sample);
+            if let Some(attempt) = attempt {
+                self.attempt_histories.lock().unwrap()
+                    .entry(history_key)
+                    .or_default()
+                    .push(attempt);
+            }
+        }
+
+        let outcome = policy.outcome(score.weighted_score, &ability, is_flagged, &behavior_data.answer_times, &user_session.accommodation);
 
-        let level = if is_flagged {
-            1 // Reduced level if flagged
+        behavior_analysis.input_consistency = input_consistency;
+        behavior_analysis.attempt_consistency = attempt_consistency;
+        behavior_analysis.consistency_score = consistency_score;
+        behavior_analysis.answer_pattern = observed.answer_pattern;
+        
+        let encrypted_score = self.generate_encrypted_score(correct_count, &quiz_type, &user_session.score_salt);
+        let session_hash = certificates::session_hash(session_id, encrypted_answers);
+        let certificate_id = self.generate_certificate_id();
+        let issued_at = now_secs();
+        let certificate = self.issuer_key.sign(CertificatePayload {
+            version: certificates::PAYLOAD_VERSION,
+            certificate_id: certificate_id.clone(),
+            quiz_type: quiz_type.clone(),
+            level: outcome.level,
+            score_commitment: certificates::score_commitment(&encrypted_score),
+            cheating_likelihood_bps: (cheating_likelihood.clamp(0.0, 1.0) * 10_000.0).round() as u16,
+            user_id: user_session.user_id.clone(),
+            issued_at,
+            expires_at: policy.expires_at(issued_at),
+            session_hash,
+        });
+        let selective = disclosure::commit(&self.issuer_key, &certificate.payload);
+        let holder_secret = {
+            let mut secret = [0u8; 32];
+            rand::rngs::OsRng.fill_bytes(&mut secret);
+            hex::encode(secret)
+        };
+        // Unrecorded certificates don't verify, so they are never minted either
+        let registered = match self.certificate_registry.register(certificate.clone(), selective, &holder_secret, outcome.passed) {
+            Ok(()) => true,
+            Err(e) => {
+                println!("⚠️  Failed to record certificate {}: {}", certificate_id, e);
+                false
+            }
+        };
+        let mint = if outcome.passed && registered {
+            self.attest_mint(&certificate, is_flagged, total_questions, correct_count)
         } else {
-            match score_percentage {
-                p if p >= 0.9 => 5,
-                p if p >= 0.7 => 4,
-                p if p >= 0.6 => 3,
-                p if p >= 0.5 => 2,
-                _ => 1,
+            None
+        };
+        let relay = match (&self.relayer, &mint, wallet_address) {
+            (Some(relayer), Some(mint), Some(wallet)) if !is_flagged => {
+                Some(match attestation::parse_address(wallet) {
+                    Some(wallet) => relayer.submit(mint.clone(), wallet),
+                    None => RelayStatus::Failed { error: format!("invalid wallet address {}", wallet) },
+                })
             }
+            _ => None,
         };
+        let accommodation = user_session.accommodation.adjustment();
 
-        let behavior_analysis = self.analyze_behavior(behavior_data, total_questions);
-        
-        let encrypted_score = self.generate_encrypted_score(correct_count, &quiz_type);
-        let certificate_id = self.generate_certificate_id(correct_count, &quiz_type, cheating_likelihood);
+        // A session yields one certificate; evaluating it again would re-issue
+        self.evaluated_sessions.lock().unwrap().insert(session_id.to_string());
+        sessions.remove(session_id);
 
         Some(QuizResponse {
-            passed: passed && !is_flagged,
+            passed: outcome.passed,
             encrypted_score,
-            level,
+            level: outcome.level,
             correct_answers: correct_count,
+            raw_score: score.raw_score,
+            weighted_score: score.weighted_score,
+            ability,
+            skill_rating,
             total_questions,
             quiz_type,
             certificate_id,
+            certificate,
+            holder_secret,
+            mint,
+            relay,
             cheating_likelihood,
             behavior_analysis,
             is_flagged,
+            min_time_violation: outcome.min_time_violation,
+            accommodation,
         })
     }
 
@@ -179,24 +609,81 @@ impl MobileFHE {
         !encrypted_answer.is_empty() && encrypted_answer.len() > 5
     }
 
-    fn analyze_behavior(&self, behavior_data: &BehaviorData, total_questions: usize) -> BehaviorAnalysis {
-        let avg_time: f32 = behavior_data.answer_times.iter().sum::<u32>() as f32 / behavior_data.answer_times.len() as f32;
+    /// Decrypts an envelope with the session's own key: the bound key if there
+    /// is one, otherwise a live key of the session's user. Another user's key
+    /// never decrypts, however the envelope names it.
+    fn decrypt_envelope(&self, session: &UserSession, envelope: &Envelope) -> Option<u8> {
+        if session.key_id.as_ref().is_some_and(|bound| *bound != envelope.key_id) {
+            return None;
+        }
+        let key = self.client_keys.owned_by(&envelope.key_id, &session.user_id, now_secs()).ok()
+            .filter(|key| key.scheme == envelope.scheme)?;
+        envelope.decrypt(&key.server_key)
+    }
+
+    /// The selected option of a single-part answer, when its scheme lets the
+    /// backend decrypt it.
+    fn decrypt_answer(&self, session: &UserSession, encrypted_answer: &str) -> Option<u8> {
+        match Ciphertext::parse(encrypted_answer) {
+            Ok(Ciphertext::Envelope(envelope)) => self.decrypt_envelope(session, &envelope),
+            _ => None,
+        }
+    }
+
+    /// Decrypts or evaluates one answer part according to its envelope's
+    /// scheme, against that part's correct option.
+    fn evaluate_encrypted_part(&self, session: &UserSession, part: &str, expected: u8, question_index: usize, question: &DynamicQuestion) -> bool {
+        match Ciphertext::parse(part) {
+            // There is no homomorphic evaluator yet, so answers the backend
+            // can't decrypt (tfhe-uint8) earn no credit
+            Ok(Ciphertext::Envelope(envelope)) => self.decrypt_envelope(session, &envelope) == Some(expected),
+            // Accepted legacy strings keep the old opaque check
+            Ok(Ciphertext::Legacy(_)) => self.simulate_encrypted_check(part, question_index, question),
+            Err(_) => false,
+        }
+    }
+
+    /// Share of an item's parts answered correctly. Multi-part answers
+    /// arrive as one ciphertext per part, separated by '|', each checked
+    /// against its own key; a wrong part count earns nothing.
+    fn simulate_encrypted_credit(&self, session: &UserSession, encrypted_answer: &str, question_index: usize, question: &DynamicQuestion) -> f32 {
+        let key = question.answer_key();
+        let parts: Vec<&str> = encrypted_answer.split(ciphertext::PART_SEPARATOR).collect();
+        if parts.len() != key.len() {
+            return 0.0;
+        }
+        let correct = parts.iter().zip(key)
+            .filter(|(part, &expected)| self.evaluate_encrypted_part(session, part, expected, question_index, question))
+            .count();
+        correct as f32 / key.len() as f32
+    }
+
+    fn analyze_behavior(
+        &self,
+        behavior_data: &BehaviorData,
+        total_questions: usize,
+        telemetry: TelemetrySummary,
+    ) -> BehaviorAnalysis {
+        let answer_times = &behavior_data.answer_times;
+        // Divide by at least one so a submission without times yields zeros, not NaN
+        let timed = answer_times.len().max(1) as f32;
+        let avg_time: f32 = answer_times.iter().map(|&t| u64::from(t)).sum::<u64>() as f32 / timed;
         
         // Calculate time consistency (variance)
-        let time_variance: f32 = behavior_data.answer_times.iter()
+        let time_variance: f32 = answer_times.iter()
             .map(|&t| (t as f32 - avg_time).powi(2))
-            .sum::<f32>() / behavior_data.answer_times.len() as f32;
+            .sum::<f32>() / timed;
         
         let time_consistency = (1.0 / (1.0 + time_variance)).min(1.0);
         
         // Calculate switch frequency
-        let total_switches: u32 = behavior_data.switch_counts.iter().sum();
-        let switch_frequency = total_switches as f32 / total_questions as f32;
+        let total_switches: u64 = behavior_data.switch_counts.iter().map(|&s| u64::from(s)).sum();
+        let switch_frequency = total_switches as f32 / total_questions.max(1) as f32;
         
         // Pattern deviation (simplified)
-        let pattern_deviation = if behavior_data.answer_times.len() > 1 {
-            let min_time = *behavior_data.answer_times.iter().min().unwrap() as f32;
-            let max_time = *behavior_data.answer_times.iter().max().unwrap() as f32;
+        let pattern_deviation = if answer_times.len() > 1 && avg_time > 0.0 {
+            let min_time = *answer_times.iter().min().unwrap() as f32;
+            let max_time = *answer_times.iter().max().unwrap() as f32;
             (max_time - min_time) / avg_time
         } else {
             0.0
@@ -207,20 +694,63 @@ impl MobileFHE {
             time_consistency,
             switch_frequency,
             pattern_deviation,
+            telemetry,
+            input_consistency: None,
+            attempt_consistency: None,
+            consistency_score: 1.0,
+            answer_pattern: None,
         }
     }
 
-    fn generate_encrypted_score(&self, correct_count: usize, quiz_type: &str) -> String {
-        let mut rng = rand::thread_rng();
-        let random_salt: u32 = rng.gen();
-        format!("enc_{}_{}_{:x}", quiz_type, correct_count, random_salt)
+    /// Salted with the session's own 128-bit salt: the commitment can't be
+    /// brute-forced, and a session always yields the same one.
+    fn generate_encrypted_score(&self, correct_count: usize, quiz_type: &str, score_salt: &str) -> String {
+        format!("enc_{}_{}_{}", quiz_type, correct_count, score_salt)
     }
 
-    fn generate_certificate_id(&self, correct_count: usize, quiz_type: &str, cheating_likelihood: f32) -> String {
-        let mut rng = rand::thread_rng();
-        let id: u32 = rng.gen();
-        let cls_code = (cheating_likelihood * 100.0) as u32;
-        format!("CERT_{}_{}_{:03}_{:08x}", quiz_type.to_uppercase(), correct_count, cls_code, id)
+    /// Opaque 128-bit id. It ends up in URLs and credential ids, so it must
+    /// say nothing about the result; the payload's session hash ties it to the session.
+    fn generate_certificate_id(&self) -> String {
+        let mut id = [0u8; 16];
+        rand::rngs::OsRng.fill_bytes(&mut id);
+        format!("CERT_{}", hex::encode(id))
+    }
+
+    /// Mint payload for a passed certificate. Each bytes32 score is handed
+    /// out once, mirroring the contract's `usedScores` check.
+    fn attest_mint(
+        &self,
+        certificate: &SignedCertificate,
+        is_flagged: bool,
+        total_questions: usize,
+        correct_count: usize,
+    ) -> Option<MintAttestation> {
+        let payload = &certificate.payload;
+        let score = attestation::score_commitment(&payload.certificate_id, &payload.score_commitment);
+        if !self.mint_commitments.lock().unwrap().insert(score) {
+            println!("⚠️  Score commitment for {} was already attested", payload.certificate_id);
+            return None;
+        }
+
+        Some(self.oracle_key.attest(MintArgs {
+            skill_type: payload.quiz_type.clone(),
+            encrypted_score: attestation::to_hex(&score),
+            level: payload.level,
+            certificate_id: payload.certificate_id.clone(),
+            cheating_likelihood: (payload.cheating_likelihood_bps / 100).min(100) as u8,
+            behavior_flagged: is_flagged,
+            total_questions: total_questions as u64,
+            correct_answers: correct_count as u64,
+        }))
+    }
+
+    /// A certificate's status, plus the stored certificate if it is currently valid.
+    fn valid_certificate(&self, certificate_id: &str) -> (CertificateStatus, Option<SignedCertificate>) {
+        let status = self.certificate_registry.lookup(&self.issuer_key.verifying_key(), certificate_id, now_secs());
+        let certificate = self.certificate_registry.entry(certificate_id)
+            .filter(|_| status.valid)
+            .map(|entry| entry.certificate);
+        (status, certificate)
     }
 
     fn get_available_quizzes(&self) -> Vec<String> {
@@ -234,25 +764,90 @@ async fn create_session(
     data: web::Data<AppState>,
 ) -> Result<HttpResponse> {
     println!("🎯 Creating new session for user: {}", req.user_id);
+    if let Some(key_id) = &req.key_id {
+        if let Err(e) = data.fhe_engine.client_keys.owned_by(key_id, &req.user_id, now_secs()) {
+            return Ok(HttpResponse::BadRequest().body(e.to_string()));
+        }
+    }
     
-    let session_id = data.fhe_engine.create_user_session(req.user_id.clone(), req.quiz_type.clone());
+    let session_id = data.fhe_engine.create_user_session(req.user_id.clone(), req.quiz_type.clone(), req.adaptive);
+    if let Some(key_id) = &req.key_id {
+        if let Err(e) = data.fhe_engine.bind_session_key(&session_id, key_id) {
+            return Ok(HttpResponse::BadRequest().body(e.to_string()));
+        }
+    }
     let questions = data.fhe_engine.get_session_questions(&session_id).unwrap_or_default();
     
     let response = SessionResponse {
         session_id,
+        adaptive: req.adaptive,
         questions,
     };
     
     Ok(HttpResponse::Ok().json(response))
 }
 
+async fn next_question(
+    req: web::Json<NextQuestionRequest>,
+    data: web::Data<AppState>,
+) -> Result<HttpResponse> {
+    let req = req.into_inner();
+    if data.fhe_engine.is_evaluated(&req.session_id) {
+        return Ok(HttpResponse::Conflict().body("Session was already evaluated"));
+    }
+    if let Err(e) = data.ciphertext_policy.check(&req.encrypted_answer) {
+        return Ok(HttpResponse::BadRequest().body(e.to_string()));
+    }
+    if let Err(e) = data.fhe_engine.check_answer_keys(&req.session_id, std::slice::from_ref(&req.encrypted_answer)) {
+        return Ok(HttpResponse::BadRequest().body(e.to_string()));
+    }
+    if let Err((_, e)) = data.fhe_engine.check_part_counts(&req.session_id, std::slice::from_ref(&req.encrypted_answer)) {
+        return Ok(HttpResponse::BadRequest().body(e));
+    }
+    match data.fhe_engine.next_question(&req.session_id, req.encrypted_answer) {
+        Some(response) => {
+            println!("🧭 Adaptive session {}: {} answered | θ = {:.2} ± {:.2}{}",
+                     response.session_id, response.items_answered,
+                     response.ability.theta, response.ability.standard_error,
+                     if response.finished { " | finished" } else { "" });
+            Ok(HttpResponse::Ok().json(response))
+        }
+        None => Ok(HttpResponse::BadRequest().body("No adaptive session in progress")),
+    }
+}
+
 async fn evaluate_quiz(
     req: web::Json<QuizRequest>,
     data: web::Data<AppState>,
 ) -> Result<HttpResponse> {
     println!("📊 Evaluating quiz with behavior analysis for session: {}", req.user_id);
+    if data.fhe_engine.is_evaluated(&req.user_id) {
+        println!("❌ Session already evaluated: {}", req.user_id);
+        return Ok(HttpResponse::Conflict().body("Session was already evaluated"));
+    }
+    if data.fhe_engine.is_unfinished_adaptive(&req.user_id) {
+        println!("❌ Adaptive session not finished: {}", req.user_id);
+        return Ok(HttpResponse::BadRequest().body("Adaptive session is not finished"));
+    }
+    if let Err((index, e)) = data.ciphertext_policy.check_all(&req.encrypted_answers) {
+        println!("❌ Rejected answer {}: {}", index, e);
+        return Ok(HttpResponse::BadRequest().body(format!("answer {}: {}", index, e)));
+    }
+    if let Err(e) = data.fhe_engine.check_answer_keys(&req.user_id, &req.encrypted_answers) {
+        println!("❌ Rejected answers for {}: {}", req.user_id, e);
+        return Ok(HttpResponse::BadRequest().body(e.to_string()));
+    }
+    if let Err((index, e)) = data.fhe_engine.check_part_counts(&req.user_id, &req.encrypted_answers) {
+        println!("❌ Rejected answer {}: {}", index, e);
+        return Ok(HttpResponse::BadRequest().body(format!("answer {}: {}", index, e)));
+    }
     
-    match data.fhe_engine.evaluate_quiz_with_behavior(&req.user_id, &req.encrypted_answers, &req.behavior_data) {
+    match data.fhe_engine.evaluate_quiz_with_behavior(
+        &req.user_id,
+        &req.encrypted_answers,
+        &req.behavior_data,
+        req.wallet_address.as_deref(),
+    ) {
         Some(response) => {
             let status = if response.passed { "PASSED" } else { "FAILED" };
             let flagged = if response.is_flagged { "🚩 FLAGGED" } else { "✅ CLEAN" };
@@ -267,6 +862,409 @@ async fn evaluate_quiz(
     }
 }
 
+async fn register_key(
+    req: web::Json<KeyRegistration>,
+    data: web::Data<AppState>,
+) -> Result<HttpResponse> {
+    let engine = &data.fhe_engine;
+    let registered = match engine.client_keys.register(&req, now_secs()) {
+        Ok(registered) => registered,
+        Err(e @ KeyError::Malformed(_)) => return Ok(HttpResponse::BadRequest().body(e.to_string())),
+        Err(e @ KeyError::TooLarge { .. }) => return Ok(HttpResponse::PayloadTooLarge().body(e.to_string())),
+        Err(e @ (KeyError::TooManyKeys { .. } | KeyError::StoreFull)) => {
+            return Ok(HttpResponse::TooManyRequests().body(e.to_string()));
+        }
+        Err(e) => return Ok(HttpResponse::Conflict().body(e.to_string())),
+    };
+    let info = &registered.info;
+    println!("🔑 Registered {} key {} for {} ({} bytes)",
+             info.scheme.id(), info.key_id, req.user_id, info.server_key_bytes + info.public_key_bytes);
+
+    if let Some(session_id) = &req.session_id {
+        if let Err(e) = engine.bind_session_key(session_id, &info.key_id) {
+            return Ok(HttpResponse::BadRequest().body(e.to_string()));
+        }
+    }
+    Ok(HttpResponse::Ok().json(registered))
+}
+
+/// The key, if the request carries the token issued when it was registered,
+/// otherwise the response to send back.
+fn authorized_key(http: &HttpRequest, data: &AppState, key_id: &str) -> std::result::Result<Arc<ClientKey>, HttpResponse> {
+    let Some(token) = bearer_token(http) else {
+        return Err(bearer_required("Key token required"));
+    };
+    data.fhe_engine.client_keys.authorized(key_id, token, now_secs()).map_err(|e| match e {
+        KeyError::InvalidToken(_) => HttpResponse::Forbidden().body(e.to_string()),
+        e => HttpResponse::NotFound().body(e.to_string()),
+    })
+}
+
+async fn get_key(
+    http: HttpRequest,
+    path: web::Path<String>,
+    data: web::Data<AppState>,
+) -> Result<HttpResponse> {
+    match authorized_key(&http, &data, &path.into_inner()) {
+        Ok(key) => Ok(HttpResponse::Ok().json(key.info())),
+        Err(denied) => Ok(denied),
+    }
+}
+
+async fn delete_key(
+    http: HttpRequest,
+    path: web::Path<String>,
+    data: web::Data<AppState>,
+) -> Result<HttpResponse> {
+    let key_id = path.into_inner();
+    if let Err(denied) = authorized_key(&http, &data, &key_id) {
+        return Ok(denied);
+    }
+    if data.fhe_engine.client_keys.remove(&key_id) {
+        println!("🗑️  Removed client key {}", key_id);
+        Ok(HttpResponse::NoContent().finish())
+    } else {
+        Ok(HttpResponse::NotFound().body(KeyError::UnknownKey(key_id).to_string()))
+    }
+}
+
+async fn bind_session_key(
+    path: web::Path<String>,
+    req: web::Json<BindKeyRequest>,
+    data: web::Data<AppState>,
+) -> Result<HttpResponse> {
+    match data.fhe_engine.bind_session_key(&path.into_inner(), &req.key_id) {
+        Ok(info) => Ok(HttpResponse::Ok().json(info)),
+        Err(e @ KeyError::UnknownSession(_)) => Ok(HttpResponse::NotFound().body(e.to_string())),
+        Err(e) => Ok(HttpResponse::BadRequest().body(e.to_string())),
+    }
+}
+
+async fn shadow_report(data: web::Data<AppState>) -> Result<HttpResponse> {
+    Ok(HttpResponse::Ok().json(data.fhe_engine.shadow.report()))
+}
+
+async fn set_accommodation(
+    http: HttpRequest,
+    req: web::Json<AccommodationRequest>,
+    data: web::Data<AppState>,
+) -> Result<HttpResponse> {
+    // Accommodations relax the detectors, so candidates can't grant their own
+    if let Some(denied) = data.require_admin(&http) {
+        return Ok(denied);
+    }
+    let req = req.into_inner();
+    println!("♿ Updating accommodation for user: {}", req.user_id);
+    let accommodation = data.fhe_engine.set_accommodation(req.user_id, req.accommodation);
+    Ok(HttpResponse::Ok().json(accommodation))
+}
+
+async fn get_accommodation(
+    http: HttpRequest,
+    user_id: web::Path<String>,
+    data: web::Data<AppState>,
+) -> Result<HttpResponse> {
+    // Accommodations reveal disability-related needs
+    if let Some(denied) = data.require_admin(&http) {
+        return Ok(denied);
+    }
+    Ok(HttpResponse::Ok().json(data.fhe_engine.get_accommodation(&user_id)))
+}
+
+async fn item_stats_report(http: HttpRequest, data: web::Data<AppState>) -> Result<HttpResponse> {
+    if let Some(denied) = data.require_admin(&http) {
+        return Ok(denied);
+    }
+    Ok(HttpResponse::Ok().json(data.fhe_engine.item_stats.lock().unwrap().report()))
+}
+
+async fn calibrate_items(http: HttpRequest, data: web::Data<AppState>) -> Result<HttpResponse> {
+    // Calibration changes the difficulties live sessions are scored with
+    if let Some(denied) = data.require_admin(&http) {
+        return Ok(denied);
+    }
+    let mut item_stats = data.fhe_engine.item_stats.lock().unwrap();
+    let updated = item_stats.calibrate();
+    println!("📐 Calibrated {} item template(s)", updated);
+    Ok(HttpResponse::Ok().json(item_stats.report()))
+}
+
+async fn get_profile(
+    user_id: web::Path<String>,
+    data: web::Data<AppState>,
+) -> Result<HttpResponse> {
+    Ok(HttpResponse::Ok().json(data.fhe_engine.get_skill_profile(&user_id)))
+}
+
+async fn issuer_public_key(data: web::Data<AppState>) -> Result<HttpResponse> {
+    Ok(HttpResponse::Ok().json(data.fhe_engine.issuer_key.public_key()))
+}
+
+async fn lookup_certificate(
+    path: web::Path<String>,
+    data: web::Data<AppState>,
+) -> Result<HttpResponse> {
+    let engine = &data.fhe_engine;
+    let status = engine.certificate_registry.lookup(&engine.issuer_key.verifying_key(), &path.into_inner(), now_secs());
+    Ok(HttpResponse::Ok().json(status))
+}
+
+async fn verify_certificate(
+    req: web::Json<SignedCertificate>,
+    data: web::Data<AppState>,
+) -> Result<HttpResponse> {
+    let engine = &data.fhe_engine;
+    let status = engine.certificate_registry.verify(&engine.issuer_key.verifying_key(), &req, now_secs());
+    Ok(HttpResponse::Ok().json(status))
+}
+
+#[derive(Debug, Deserialize)]
+struct RevokeRequest {
+    reason: String,
+}
+
+async fn revoke_certificate(
+    http: HttpRequest,
+    path: web::Path<String>,
+    req: web::Json<RevokeRequest>,
+    data: web::Data<AppState>,
+) -> Result<HttpResponse> {
+    if let Some(denied) = data.require_admin(&http) {
+        return Ok(denied);
+    }
+    let certificate_id = path.into_inner();
+    match data.fhe_engine.certificate_registry.revoke(&certificate_id, req.into_inner().reason, now_secs()) {
+        Ok(revocation) => {
+            println!("🚫 Revoked certificate {}: {}", certificate_id, revocation.reason);
+            Ok(HttpResponse::Ok().json(revocation))
+        }
+        Err(e @ RevocationError::UnknownCertificate(_)) => Ok(HttpResponse::NotFound().body(e.to_string())),
+        Err(e @ RevocationError::AlreadyRevoked(_)) => Ok(HttpResponse::Conflict().body(e.to_string())),
+        Err(e @ RevocationError::Storage(_)) => Ok(HttpResponse::InternalServerError().body(e.to_string())),
+    }
+}
+
+async fn certificate_presentation(
+    http: HttpRequest,
+    path: web::Path<String>,
+    req: web::Json<PresentationRequest>,
+    data: web::Data<AppState>,
+) -> Result<HttpResponse> {
+    let engine = &data.fhe_engine;
+    let certificate_id = path.into_inner();
+    // Only the holder may open attributes; the certificate id alone is public
+    match bearer_token(&http) {
+        Some(secret) if engine.certificate_registry.is_holder(&certificate_id, secret) => {}
+        Some(_) => return Ok(HttpResponse::Forbidden().body("Invalid holder secret")),
+        None => return Ok(bearer_required("Holder secret required")),
+    }
+    let (status, certificate) = engine.valid_certificate(&certificate_id);
+    if certificate.is_none() {
+        return Ok(HttpResponse::Conflict().json(status));
+    }
+    // Every presentation opens a batch no earlier one used, so verifiers can't link them
+    let batch = match engine.certificate_registry.take_batch(&certificate_id, &engine.issuer_key) {
+        Ok(Some(batch)) => batch,
+        Ok(None) => return Ok(HttpResponse::Conflict().body("Certificate was issued without attribute commitments")),
+        Err(e) => return Ok(HttpResponse::InternalServerError().body(format!("Failed to record presentation: {}", e))),
+    };
+    match batch.present(&engine.issuer_key, &req) {
+        Ok(presentation) => Ok(HttpResponse::Ok().json(presentation)),
+        Err(e @ DisclosureError::LevelNotReached(_)) => Ok(HttpResponse::Conflict().body(e.to_string())),
+        Err(e) => Ok(HttpResponse::BadRequest().body(e.to_string())),
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct PresentationStatus {
+    valid: bool,
+    reason: Option<String>, // why it is not valid
+    attributes: BTreeMap<String, serde_json::Value>,
+}
+
+/// A presentation with the challenge the verifier issued for it.
+#[derive(Debug, Deserialize)]
+struct PresentationCheck {
+    presentation: Presentation,
+    audience: String,
+    nonce: String,
+}
+
+async fn verify_presentation(
+    req: web::Json<PresentationCheck>,
+    data: web::Data<AppState>,
+) -> Result<HttpResponse> {
+    let engine = &data.fhe_engine;
+    let key = engine.issuer_key.verifying_key();
+    let now = now_secs();
+    let presentation = &req.presentation;
+    let (attributes, reason) = match disclosure::verify(&key, presentation, &req.audience, &req.nonce, now) {
+        Ok(attributes) => {
+            // Signature and expiry check out offline; revocation needs the registry
+            let reason = match engine.certificate_registry.lookup_handle(&key, &presentation.commitments.handle, now) {
+                Some(status) => status.reason,
+                None => Some("certificate is not in the registry".to_string()),
+            };
+            (attributes, reason)
+        }
+        Err(e) => (BTreeMap::new(), Some(e.to_string())),
+    };
+    Ok(HttpResponse::Ok().json(PresentationStatus {
+        valid: reason.is_none(),
+        reason,
+        attributes,
+    }))
+}
+
+#[derive(Debug, Serialize)]
+struct CredentialResponse {
+    format: &'static str,
+    issuer: String,
+    credential: String,
+}
+
+async fn certificate_credential(
+    path: web::Path<String>,
+    data: web::Data<AppState>,
+) -> Result<HttpResponse> {
+    let engine = &data.fhe_engine;
+    match engine.valid_certificate(&path.into_inner()) {
+        (_, Some(certificate)) => Ok(HttpResponse::Ok().json(CredentialResponse {
+            format: credentials::JWT_TYPE,
+            issuer: credentials::issuer_did(&engine.issuer_key.verifying_key()),
+            credential: credentials::issue_jwt(&engine.issuer_key, &certificate, &data.public_base_url),
+        })),
+        (status, None) => Ok(HttpResponse::Conflict().json(status)),
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct OpenBadgeResponse {
+    format: &'static str,
+    credential: String,
+    document: AchievementCredential, // the signed payload, for display
+}
+
+async fn certificate_open_badge(
+    path: web::Path<String>,
+    data: web::Data<AppState>,
+) -> Result<HttpResponse> {
+    let engine = &data.fhe_engine;
+    let (status, certificate) = engine.valid_certificate(&path.into_inner());
+    let Some(certificate) = certificate else {
+        return Ok(HttpResponse::Conflict().json(status));
+    };
+    let Some(config) = engine.quizzes.get(&certificate.payload.quiz_type) else {
+        return Ok(HttpResponse::NotFound().body("Quiz for this certificate is no longer available"));
+    };
+    let (document, credential) = open_badges::issue(&engine.issuer_key, &certificate, config);
+    Ok(HttpResponse::Ok().json(OpenBadgeResponse {
+        format: credentials::JWT_TYPE,
+        credential,
+        document,
+    }))
+}
+
+/// Rendering input for a stored certificate, linking back to its lookup endpoint.
+fn badge_view<'a>(data: &AppState, certificate: &'a SignedCertificate) -> rendering::BadgeView<'a> {
+    let payload = &certificate.payload;
+    let flagged = data.fhe_engine.quizzes.get(&payload.quiz_type)
+        .map(|config| config.scoring.is_flagged(
+            payload.cheating_likelihood_bps as f32 / 10_000.0,
+            &data.fhe_engine.cheating_model,
+        ))
+        .unwrap_or(false);
+    rendering::BadgeView {
+        certificate,
+        flagged,
+        verification_url: format!("{}/certificates/{}", data.public_base_url, payload.certificate_id),
+    }
+}
+
+async fn certificate_badge_svg(
+    path: web::Path<String>,
+    data: web::Data<AppState>,
+) -> Result<HttpResponse> {
+    match data.fhe_engine.valid_certificate(&path.into_inner()) {
+        (_, Some(certificate)) => Ok(HttpResponse::Ok()
+            .content_type("image/svg+xml")
+            .body(badge_view(&data, &certificate).svg())),
+        (status, None) => Ok(HttpResponse::Conflict().json(status)),
+    }
+}
+
+async fn certificate_pdf(
+    path: web::Path<String>,
+    data: web::Data<AppState>,
+) -> Result<HttpResponse> {
+    match data.fhe_engine.valid_certificate(&path.into_inner()) {
+        (_, Some(certificate)) => Ok(HttpResponse::Ok()
+            .content_type("application/pdf")
+            .insert_header(("Content-Disposition", format!(
+                "inline; filename=\"{}.pdf\"", certificate.payload.certificate_id,
+            )))
+            .body(badge_view(&data, &certificate).pdf())),
+        (status, None) => Ok(HttpResponse::Conflict().json(status)),
+    }
+}
+
+async fn get_achievement(
+    path: web::Path<String>,
+    data: web::Data<AppState>,
+) -> Result<HttpResponse> {
+    match data.fhe_engine.quizzes.get(&path.into_inner()) {
+        Some(config) => Ok(HttpResponse::Ok().json(open_badges::achievement(config))),
+        None => Ok(HttpResponse::NotFound().body("Quiz not found")),
+    }
+}
+
+async fn revocation_list(data: web::Data<AppState>) -> Result<HttpResponse> {
+    let engine = &data.fhe_engine;
+    let snapshot = engine.certificate_registry.revocation_list(now_secs()).sign(&engine.issuer_key);
+    Ok(HttpResponse::Ok().json(snapshot))
+}
+
+async fn relay_status(
+    path: web::Path<String>,
+    data: web::Data<AppState>,
+) -> Result<HttpResponse> {
+    let certificate_id = path.into_inner();
+    match data.fhe_engine.relayer.as_ref().and_then(|relayer| relayer.job(&certificate_id)) {
+        Some(job) => Ok(HttpResponse::Ok().json(job)),
+        None => Ok(HttpResponse::NotFound().body("No relayed mint for this certificate")),
+    }
+}
+
+async fn retry_relay(
+    http: HttpRequest,
+    path: web::Path<String>,
+    data: web::Data<AppState>,
+) -> Result<HttpResponse> {
+    if let Some(denied) = data.require_admin(&http) {
+        return Ok(denied);
+    }
+    let certificate_id = path.into_inner();
+    match data.fhe_engine.relayer.as_ref().and_then(|relayer| relayer.retry(&certificate_id)) {
+        Some(Ok(status)) => {
+            println!("🔁 Retrying badge transfer for certificate {}", certificate_id);
+            Ok(HttpResponse::Accepted().json(status))
+        }
+        Some(Err(status)) => Ok(HttpResponse::Conflict().json(status)),
+        None => Ok(HttpResponse::NotFound().body("No relayed mint for this certificate")),
+    }
+}
+
+async fn suspicious_tokens(data: web::Data<AppState>) -> Result<HttpResponse> {
+    match &data.indexer {
+        Some(indexer) => Ok(HttpResponse::Ok().json(indexer.report())),
+        None => Ok(HttpResponse::NotFound().body("Chain indexer is not configured")),
+    }
+}
+
+async fn oracle_info(data: web::Data<AppState>) -> Result<HttpResponse> {
+    Ok(HttpResponse::Ok().json(data.fhe_engine.oracle_key.info()))
+}
+
 async fn get_quizzes(data: web::Data<AppState>) -> Result<HttpResponse> {
     let available_quizzes = data.fhe_engine.get_available_quizzes();
     Ok(HttpResponse::Ok().json(available_quizzes))
@@ -293,8 +1291,47 @@ async fn health_check(data: web::Data<AppState>) -> Result<HttpResponse> {
     Ok(HttpResponse::Ok().json(response))
 }
 
+fn now_secs() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_secs()
+}
+
 struct AppState {
     fhe_engine: MobileFHE,
+    indexer: Option<ChainIndexer>,
+    public_base_url: String, // prefix for verification links on rendered certificates
+    ciphertext_policy: CiphertextPolicy,
+    admin_token_hash: Option<[u8; 32]>, // SHA-256 of ADMIN_TOKEN; admin endpoints are off without it
+}
+
+fn bearer_token(http: &HttpRequest) -> Option<&str> {
+    http.headers().get("Authorization")
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+}
+
+fn bearer_required(message: &str) -> HttpResponse {
+    HttpResponse::Unauthorized()
+        .insert_header(("WWW-Authenticate", "Bearer"))
+        .body(message.to_string())
+}
+
+impl AppState {
+    /// None if the request carries the admin bearer token, otherwise the
+    /// 401/403 response to send back.
+    fn require_admin(&self, http: &HttpRequest) -> Option<HttpResponse> {
+        let Some(expected) = &self.admin_token_hash else {
+            return Some(HttpResponse::Forbidden().body("Admin endpoints are disabled; set ADMIN_TOKEN"));
+        };
+        match bearer_token(http) {
+            // Comparing digests keeps the comparison time independent of the token
+            Some(token) if Sha256::digest(token.as_bytes()).as_slice() == expected => None,
+            Some(_) => Some(HttpResponse::Forbidden().body("Invalid admin token")),
+            None => Some(bearer_required("Admin token required")),
+        }
+    }
 }
 
 #[actix_web::main]
@@ -304,17 +1341,245 @@ async fn main() -> std::io::Result<()> {
     println!("   Features: Dynamic Questions + Anti-Cheating + Behavior Analysis");
     println!("   Server: http://0.0.0.0:8080");
 
+    // Tuned detector weights from the calibrate tool, if configured
+    let cheating_model = match std::env::var("CHEATING_MODEL_PATH") {
+        Ok(path) => {
+            let model = CheatingModel::load(std::path::Path::new(&path))?;
+            println!("   Cheating model: {} (flag threshold {:.2})", path, model.flag_threshold);
+            model
+        }
+        Err(_) => CheatingModel::default(),
+    };
+
+    // Candidate models evaluated in shadow, if configured
+    let shadow = match std::env::var("SHADOW_MODELS_PATH") {
+        Ok(path) => {
+            let shadow = ShadowEvaluator::load(std::path::Path::new(&path))?;
+            println!("   Shadow models: {} candidate(s) from {}", shadow.len(), path);
+            shadow
+        }
+        Err(_) => ShadowEvaluator::default(),
+    };
+
+    // Per-quiz level bands, thresholds and flag handling, if configured
+    let policies = match std::env::var("QUIZ_POLICIES_PATH") {
+        Ok(path) => {
+            let policies = scoring::load_policies(std::path::Path::new(&path))?;
+            println!("   Scoring policies: {} override(s) from {}", policies.len(), path);
+            policies
+        }
+        Err(_) => HashMap::new(),
+    };
+
+    // Certificate signing key; without one, certificates only verify until restart
+    let issuer_key = match std::env::var("ISSUER_KEY_PATH") {
+        Ok(path) => {
+            let path = std::path::Path::new(&path);
+            let key = if path.exists() {
+                IssuerKey::load(path)?
+            } else {
+                let key = IssuerKey::generate();
+                key.save(path)?;
+                key
+            };
+            println!("   Issuer key: {} (key id {})", path.display(), key.key_id());
+            key
+        }
+        Err(_) => {
+            let key = IssuerKey::generate();
+            println!("⚠️  ISSUER_KEY_PATH not set, using ephemeral issuer key {}", key.key_id());
+            key
+        }
+    };
+
+    // Issued certificates, persisted across restarts if configured
+    let certificate_registry = match std::env::var("CERTIFICATE_REGISTRY_PATH") {
+        Ok(path) => {
+            let registry = CertificateRegistry::open(std::path::Path::new(&path))?;
+            println!("   Certificate registry: {} certificate(s) in {}", registry.len(), path);
+            registry
+        }
+        Err(_) => CertificateRegistry::default(),
+    };
+    let certificate_registry = Arc::new(certificate_registry);
+
+    // Oracle key and EIP-712 domain for mint attestations
+    let chain_id = match std::env::var("MINT_CHAIN_ID") {
+        Ok(id) => id.parse().map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidInput, e))?,
+        Err(_) => attestation::DEFAULT_CHAIN_ID,
+    };
+    let passport_contract = match std::env::var("PASSPORT_CONTRACT_ADDRESS") {
+        Ok(address) => attestation::parse_address(&address).ok_or_else(|| std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            "PASSPORT_CONTRACT_ADDRESS must be a 0x-prefixed 20-byte address",
+        ))?,
+        Err(_) => [0u8; 20],
+    };
+    let domain = MintDomain::new(chain_id, passport_contract);
+    let oracle_key = match std::env::var("ORACLE_KEY_PATH") {
+        Ok(path) => {
+            let path = std::path::Path::new(&path);
+            let key = if path.exists() {
+                OracleKey::load(path, domain)?
+            } else {
+                let key = OracleKey::generate(domain);
+                key.save(path)?;
+                key
+            };
+            println!("   Oracle key: {} (address {})", path.display(), key.address());
+            key
+        }
+        Err(_) => {
+            let key = OracleKey::generate(domain);
+            println!("⚠️  ORACLE_KEY_PATH not set, using ephemeral oracle {}", key.address());
+            key
+        }
+    };
+
+    // Backend minting through a node-managed account, if configured
+    let relayer = match std::env::var("RELAYER_RPC_URL") {
+        Ok(rpc_url) => {
+            let address = |name: &str| {
+                std::env::var(name).ok()
+                    .and_then(|address| attestation::parse_address(&address))
+                    .ok_or_else(|| std::io::Error::new(
+                        std::io::ErrorKind::InvalidInput,
+                        format!("RELAYER_RPC_URL requires {} as a 0x-prefixed address", name),
+                    ))
+            };
+            let config = RelayerConfig {
+                rpc_url,
+                from: address("RELAYER_FROM")?,
+                contract: address("PASSPORT_CONTRACT_ADDRESS")?,
+                mint_fee_wei: match std::env::var("MINT_FEE_WEI") {
+                    Ok(fee) => fee.parse().map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidInput, e))?,
+                    Err(_) => relayer::DEFAULT_MINT_FEE_WEI,
+                },
+            };
+            println!("   Mint relayer: {} from {}", config.rpc_url, attestation::to_hex(&config.from));
+            Some(Relayer::start(config, certificate_registry.clone()))
+        }
+        Err(_) => None,
+    };
+
+    // BadgeMinted indexer checking mints against the registry, if configured
+    let indexer = match std::env::var("INDEXER_RPC_URL") {
+        Ok(rpc_url) => {
+            let contract = std::env::var("PASSPORT_CONTRACT_ADDRESS").ok()
+                .and_then(|address| attestation::parse_address(&address))
+                .ok_or_else(|| std::io::Error::new(
+                    std::io::ErrorKind::InvalidInput,
+                    "INDEXER_RPC_URL requires PASSPORT_CONTRACT_ADDRESS as a 0x-prefixed address",
+                ))?;
+            let number = |name: &str, default: u64| match std::env::var(name) {
+                Ok(value) => value.parse().map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidInput, e)),
+                Err(_) => Ok(default),
+            };
+            let config = IndexerConfig {
+                rpc_url,
+                contract,
+                start_block: number("INDEXER_START_BLOCK", 0)?,
+                poll_interval: std::time::Duration::from_secs(number("INDEXER_POLL_SECONDS", 15)?),
+            };
+            println!("   Chain indexer: {} from block {}", config.rpc_url, config.start_block);
+            Some(ChainIndexer::start(config, certificate_registry.clone()))
+        }
+        Err(_) => None,
+    };
+
+    // Where printed certificates point verifiers
+    let public_base_url = std::env::var("PUBLIC_BASE_URL")
+        .map(|url| url.trim_end_matches('/').to_string())
+        .unwrap_or_else(|_| "http://localhost:8080".to_string());
+    println!("   Verification links: {}/certificates/{{id}}", public_base_url);
+
+    // Pre-envelope answer strings, which may carry the answer in plaintext
+    let ciphertext_policy = CiphertextPolicy {
+        accept_legacy: std::env::var("ACCEPT_LEGACY_CIPHERTEXTS").is_ok_and(|v| v == "1" || v == "true"),
+    };
+    if ciphertext_policy.accept_legacy {
+        println!("⚠️  Accepting legacy ciphertexts; plaintext answers are readable in transit");
+    }
+
+    // Size and lifetime limits for registered client keys
+    let key_limits = KeyLimits {
+        max_key_bytes: match std::env::var("CLIENT_KEY_MAX_BYTES") {
+            Ok(bytes) => bytes.parse().map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidInput, e))?,
+            Err(_) => client_keys::DEFAULT_MAX_KEY_BYTES,
+        },
+        ttl_secs: match std::env::var("CLIENT_KEY_TTL_SECONDS") {
+            Ok(secs) => secs.parse().map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidInput, e))?,
+            Err(_) => client_keys::DEFAULT_KEY_TTL_SECS,
+        },
+        ..KeyLimits::default()
+    };
+    println!("   Client keys: up to {} bytes, expiring after {}s", key_limits.max_key_bytes, key_limits.ttl_secs);
+
+    let fhe_engine = MobileFHE::new(
+        cheating_model,
+        shadow,
+        issuer_key,
+        certificate_registry,
+        oracle_key,
+        relayer,
+        policies,
+        key_limits,
+    );
+
+    // Bearer token for administrative endpoints
+    let admin_token_hash = std::env::var("ADMIN_TOKEN").ok()
+        .filter(|token| !token.is_empty())
+        .map(|token| Sha256::digest(token.as_bytes()).into());
+    if admin_token_hash.is_none() {
+        println!("⚠️  ADMIN_TOKEN not set, admin endpoints are disabled");
+    }
+
     let app_data = web::Data::new(AppState {
-        fhe_engine: MobileFHE::new(),
+        indexer,
+        admin_token_hash,
+        public_base_url,
+        ciphertext_policy,
+        fhe_engine,
     });
+    // Base64 inflates keys by a third; leave room for the other fields
+    let key_payload_limit = key_limits.max_key_bytes / 3 * 4 + 64 * 1024;
 
     HttpServer::new(move || {
         App::new()
             .app_data(app_data.clone())
             .route("/create-session", web::post().to(create_session))
+            .service(web::resource("/keys")
+                .app_data(web::JsonConfig::default().limit(key_payload_limit))
+                .route(web::post().to(register_key)))
+            .route("/keys/{key_id}", web::get().to(get_key))
+            .route("/keys/{key_id}", web::delete().to(delete_key))
+            .route("/sessions/{session_id}/key", web::post().to(bind_session_key))
             .route("/evaluate-quiz", web::post().to(evaluate_quiz))
+            .route("/next-question", web::post().to(next_question))
             .route("/quizzes", web::get().to(get_quizzes))
             .route("/health", web::get().to(health_check))
+            .route("/shadow-report", web::get().to(shadow_report))
+            .route("/item-stats", web::get().to(item_stats_report))
+            .route("/item-stats/calibrate", web::post().to(calibrate_items))
+            .route("/accommodations", web::post().to(set_accommodation))
+            .route("/accommodations/{user_id}", web::get().to(get_accommodation))
+            .route("/profile/{user_id}", web::get().to(get_profile))
+            .route("/issuer/public-key", web::get().to(issuer_public_key))
+            .route("/oracle", web::get().to(oracle_info))
+            .route("/relayer/{certificate_id}", web::get().to(relay_status))
+            .route("/relayer/{certificate_id}/retry", web::post().to(retry_relay))
+            .route("/chain/suspicious-tokens", web::get().to(suspicious_tokens))
+            .route("/certificates/verify", web::post().to(verify_certificate))
+            .route("/certificates/revocations", web::get().to(revocation_list))
+            .route("/certificates/presentations/verify", web::post().to(verify_presentation))
+            .route("/certificates/{certificate_id}/revoke", web::post().to(revoke_certificate))
+            .route("/certificates/{certificate_id}/presentation", web::post().to(certificate_presentation))
+            .route("/certificates/{certificate_id}/credential", web::get().to(certificate_credential))
+            .route("/certificates/{certificate_id}/open-badge", web::get().to(certificate_open_badge))
+            .route("/certificates/{certificate_id}/badge.svg", web::get().to(certificate_badge_svg))
+            .route("/certificates/{certificate_id}/certificate.pdf", web::get().to(certificate_pdf))
+            .route("/achievements/{quiz_type}", web::get().to(get_achievement))
+            .route("/certificates/{certificate_id}", web::get().to(lookup_certificate))
             .route("/", web::get().to(|| async { 
                 HttpResponse::Ok().body(
                     "🛡️ Private Proof of Talent - Advanced FHE Backend v3.0\n\n\
@@ -326,9 +1591,36 @@ async fn main() -> std::io::Result<()> {
                     • FHE-Based Consistency Checks\n\n\
                     Endpoints:\n\
                     POST /create-session - Create assessment session\n\
+                    POST /keys - Register a client public/server key\n\
+                    GET  /keys/{key_id} - Client key metadata and expiry (key token)\n\
+                    DELETE /keys/{key_id} - Remove a client key (key token)\n\
+                    POST /sessions/{session_id}/key - Bind a session to a client key\n\
                     POST /evaluate-quiz - Evaluate with behavior analysis\n\
+                    POST /next-question - Answer an adaptive item and get the next\n\
                     GET  /quizzes       - Get available quizzes\n\
                     GET  /health        - Health check\n\
+                    GET  /shadow-report - Shadow vs live detector disagreement\n\
+                    GET  /item-stats    - Item and template statistics (admin)\n\
+                    POST /item-stats/calibrate - Recalibrate template difficulty and timing (admin)\n\
+                    POST /accommodations - Set a candidate's accommodation profile (admin)\n\
+                    GET  /accommodations/{user_id} - Get a candidate's accommodation profile (admin)\n\
+                    GET  /profile/{user_id} - Skill ratings per quiz category\n\
+                    GET  /issuer/public-key - Key for verifying certificate signatures\n\
+                    GET  /oracle        - Mint oracle address and EIP-712 domain\n\
+                    GET  /relayer/{certificate_id} - Status of a relayed badge mint\n\
+                    POST /relayer/{certificate_id}/retry - Retry a failed badge transfer (admin)\n\
+                    GET  /chain/suspicious-tokens - Minted badges that don't match issued certificates\n\
+                    GET  /certificates/{certificate_id} - Look up an issued certificate\n\
+                    POST /certificates/verify - Verify a presented certificate\n\
+                    GET  /certificates/revocations - Signed revocation list snapshot\n\
+                    POST /certificates/{certificate_id}/revoke - Revoke an issued certificate (admin)\n\
+                    POST /certificates/{certificate_id}/presentation - Disclose chosen attributes to a verifier's audience and nonce, e.g. level >= 4 (holder secret)\n\
+                    POST /certificates/presentations/verify - Verify a selective-disclosure presentation against its audience and nonce\n\
+                    GET  /certificates/{certificate_id}/credential - Export as a W3C Verifiable Credential (JWT)\n\
+                    GET  /certificates/{certificate_id}/open-badge - Export as an Open Badges 3.0 AchievementCredential\n\
+                    GET  /certificates/{certificate_id}/badge.svg - Badge image matching the on-chain SVG\n\
+                    GET  /certificates/{certificate_id}/certificate.pdf - Printable certificate with a verification QR code\n\
+                    GET  /achievements/{quiz_type} - Open Badges achievement definition for a quiz\n\
                     GET  /              - This message"
                 ) 
             }))
@@ -0,0 +1,244 @@
+use serde::{Deserialize, Serialize};
+use crate::certificates::{IssuerKey, SignedCertificate};
+use crate::credentials::{self, CredentialError};
+use crate::quiz_types::QuizConfig;
+use crate::scoring;
+
+pub const OPEN_BADGES_CONTEXT: &str = "https://purl.imsglobal.org/spec/ob/v3p0/context-3.0.3.json";
+pub const ISSUER_NAME: &str = "Private Proof of Talent";
+
+/// Open Badges 3.0 achievement definition for one quiz.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Achievement {
+    pub id: String,
+    #[serde(rename = "type")]
+    pub types: Vec<String>,
+    pub achievement_type: String,
+    pub name: String,
+    pub description: String,
+    pub criteria: Criteria,
+    pub tag: Vec<String>,
+    pub result_description: Vec<ResultDescription>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct Criteria {
+    pub narrative: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ResultDescription {
+    pub id: String,
+    #[serde(rename = "type")]
+    pub types: Vec<String>,
+    pub name: String,
+    pub result_type: String,
+    pub rubric_criterion_level: Vec<RubricCriterionLevel>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct RubricCriterionLevel {
+    pub id: String,
+    #[serde(rename = "type")]
+    pub types: Vec<String>,
+    pub name: String,
+    pub description: String,
+    pub level: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct IssuerProfile {
+    pub id: String,
+    #[serde(rename = "type")]
+    pub types: Vec<String>,
+    pub name: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct AchievementResult {
+    #[serde(rename = "type")]
+    pub types: Vec<String>,
+    pub result_description: String,
+    pub achieved_level: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct AchievementSubject {
+    pub id: String,
+    #[serde(rename = "type")]
+    pub types: Vec<String>,
+    pub achievement: Achievement,
+    pub result: Vec<AchievementResult>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct AchievementCredential {
+    #[serde(rename = "@context")]
+    pub context: Vec<String>,
+    pub id: String,
+    #[serde(rename = "type")]
+    pub types: Vec<String>,
+    pub issuer: IssuerProfile,
+    pub name: String,
+    pub valid_from: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub valid_until: Option<String>,
+    pub credential_subject: AchievementSubject,
+}
+
+fn achievement_id(quiz_type: &str) -> String {
+    format!("urn:ppot:achievement:{}", quiz_type)
+}
+
+fn level_id(quiz_type: &str, level: u8) -> String {
+    format!("{}:level:{}", achievement_id(quiz_type), level)
+}
+
+fn percent(score: f32) -> String {
+    format!("{}%", (score * 100.0).round())
+}
+
+/// Achievement for a quiz, with one rubric level per level band. Levels come
+/// from ability bands when the policy has them, as in `ScoringPolicy::outcome`.
+pub fn achievement(config: &QuizConfig) -> Achievement {
+    let policy = &config.scoring;
+    let skill = &config.quiz_type;
+
+    let mut bands: Vec<(u8, String)> = if policy.ability_bands.is_empty() {
+        policy.level_bands.iter()
+            .map(|band| (band.level, format!("score of at least {}", percent(band.min_score))))
+            .collect()
+    } else {
+        policy.ability_bands.iter()
+            .map(|band| (band.level, format!("ability estimate of at least {:.2} logits", band.min_ability)))
+            .collect()
+    };
+    bands.sort_by_key(|band| std::cmp::Reverse(band.0));
+
+    let mut narrative = format!(
+        "Complete the {} assessment with a weighted score of at least {} without being flagged for cheating.",
+        skill, percent(policy.pass_threshold),
+    );
+    for (level, requirement) in &bands {
+        narrative.push_str(&format!(" {} (level {}): {}.", scoring::level_name(*level), level, requirement));
+    }
+
+    Achievement {
+        id: achievement_id(skill),
+        types: vec!["Achievement".to_string()],
+        achievement_type: "Certificate".to_string(),
+        name: format!("{} skill certificate", skill),
+        description: format!("Passed the privacy-preserving {} assessment.", skill),
+        criteria: Criteria { narrative },
+        tag: vec![skill.clone()],
+        result_description: vec![ResultDescription {
+            id: format!("{}:result:level", achievement_id(skill)),
+            types: vec!["ResultDescription".to_string()],
+            name: "Level".to_string(),
+            result_type: "RubricCriterionLevel".to_string(),
+            rubric_criterion_level: bands.into_iter()
+                .map(|(level, requirement)| RubricCriterionLevel {
+                    id: level_id(skill, level),
+                    types: vec!["RubricCriterionLevel".to_string()],
+                    name: scoring::level_name(level).to_string(),
+                    description: requirement,
+                    level: level.to_string(),
+                })
+                .collect(),
+        }],
+    }
+}
+
+impl AchievementCredential {
+    pub fn from_certificate(certificate: &SignedCertificate, config: &QuizConfig, issuer: String) -> Self {
+        let payload = &certificate.payload;
+        let achievement = achievement(config);
+        AchievementCredential {
+            context: vec![credentials::CREDENTIALS_CONTEXT.to_string(), OPEN_BADGES_CONTEXT.to_string()],
+            id: format!("urn:ppot:certificate:{}:badge", payload.certificate_id),
+            types: vec!["VerifiableCredential".to_string(), "AchievementCredential".to_string()],
+            issuer: IssuerProfile {
+                id: issuer,
+                types: vec!["Profile".to_string()],
+                name: ISSUER_NAME.to_string(),
+            },
+            name: format!("{} — {}", achievement.name, scoring::level_name(payload.level)),
+            valid_from: credentials::format_timestamp(payload.issued_at),
+            valid_until: payload.expires_at.map(credentials::format_timestamp),
+            credential_subject: AchievementSubject {
+                id: format!("urn:ppot:user:{}", payload.user_id),
+                types: vec!["AchievementSubject".to_string()],
+                result: vec![AchievementResult {
+                    types: vec!["Result".to_string()],
+                    result_description: achievement.result_description[0].id.clone(),
+                    achieved_level: level_id(&payload.quiz_type, payload.level),
+                }],
+                achievement,
+            },
+        }
+    }
+}
+
+/// The credential and its VC-JWT signed by the issuer key.
+pub fn issue(key: &IssuerKey, certificate: &SignedCertificate, config: &QuizConfig) -> (AchievementCredential, String) {
+    let credential = AchievementCredential::from_certificate(
+        certificate,
+        config,
+        credentials::issuer_did(&key.verifying_key()),
+    );
+    let jwt = credentials::sign_jwt(key, &credential);
+    (credential, jwt)
+}
+
+/// Verifies a signed AchievementCredential and its validity window at `now`.
+pub fn verify(jwt: &str, now: u64) -> Result<AchievementCredential, CredentialError> {
+    let (signer, credential): (String, AchievementCredential) = credentials::decode_jwt(jwt)?;
+    if signer != credential.issuer.id {
+        return Err(CredentialError::IssuerMismatch);
+    }
+    credentials::check_validity(&credential.valid_from, credential.valid_until.as_deref(), now)?;
+    Ok(credential)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::certificates::tests::payload;
+
+    #[test]
+    fn issued_badge_verifies() {
+        let key = IssuerKey::generate();
+        let config = QuizConfig::math_quiz();
+        let (credential, jwt) = issue(&key, &key.sign(payload()), &config);
+
+        assert_eq!(verify(&jwt, 1_750_000_000), Ok(credential.clone()));
+        let result = &credential.credential_subject.result[0];
+        assert_eq!(result.achieved_level, "urn:ppot:achievement:math:level:4");
+        assert!(credential.credential_subject.achievement.result_description[0].rubric_criterion_level.iter()
+            .any(|level| level.id == result.achieved_level));
+    }
+
+    #[test]
+    fn tampered_or_expired_badge_is_rejected() {
+        let key = IssuerKey::generate();
+        let config = QuizConfig::math_quiz();
+        let (mut credential, jwt) = issue(&key, &key.sign(payload()), &config);
+        assert_eq!(verify(&jwt, 1_800_000_000), Err(CredentialError::Expired));
+
+        // A badge signed for one level cannot be edited to claim another
+        credential.credential_subject.result[0].achieved_level = level_id("math", 5);
+        let original: Vec<&str> = jwt.split('.').collect();
+        let edited = credentials::sign_jwt(&key, &credential);
+        let spliced = format!("{}.{}.{}", original[0], edited.split('.').nth(1).unwrap(), original[2]);
+        assert_eq!(verify(&spliced, 1_750_000_000), Err(CredentialError::InvalidSignature));
+
+        // Valid signature, but by a key other than the named issuer
+        let other = IssuerKey::generate();
+        let resigned = credentials::sign_jwt(&other, &credential);
+        assert_eq!(verify(&resigned, 1_750_000_000), Err(CredentialError::IssuerMismatch));
+    }
+}
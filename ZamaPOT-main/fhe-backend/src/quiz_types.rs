@@ -1,10 +1,12 @@
 use serde::{Deserialize, Serialize};
+use std::fmt;
+use crate::scoring::ScoringPolicy;
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct QuizConfig {
     pub quiz_type: String,
     pub questions: Vec<Question>,
-    pub passing_score: f32,
+    pub scoring: ScoringPolicy,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -15,11 +17,60 @@ pub struct Question {
     pub correct_answer: u8,
 }
 
+#[derive(Debug, Clone, PartialEq)]
+pub enum BankLintError {
+    Empty,
+    AnswerOutOfRange { question_id: u32 },
+    PositionBias { position: u8, count: usize, total: usize },
+}
+
+impl fmt::Display for BankLintError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            BankLintError::Empty => write!(f, "quiz has no questions"),
+            BankLintError::AnswerOutOfRange { question_id } => {
+                write!(f, "question {} has a correct answer outside its options", question_id)
+            }
+            BankLintError::PositionBias { position, count, total } => write!(
+                f,
+                "{} of {} correct answers are at option {}",
+                count, total, position
+            ),
+        }
+    }
+}
+
 impl QuizConfig {
+    /// Rejects banks a position-guessing strategy could pass: no single
+    /// option position may hold a majority of the correct answers.
+    pub fn lint(&self) -> Result<(), BankLintError> {
+        if self.questions.is_empty() {
+            return Err(BankLintError::Empty);
+        }
+
+        let mut position_counts = vec![0usize; self.questions.iter().map(|q| q.options.len()).max().unwrap_or(0)];
+        for question in &self.questions {
+            let position = question.correct_answer as usize;
+            if position >= question.options.len() {
+                return Err(BankLintError::AnswerOutOfRange { question_id: question.id });
+            }
+            position_counts[position] += 1;
+        }
+
+        let total = self.questions.len();
+        let (position, &count) = position_counts.iter().enumerate()
+            .max_by_key(|(_, &count)| count)
+            .unwrap();
+        if total > 1 && count * 2 > total {
+            return Err(BankLintError::PositionBias { position: position as u8, count, total });
+        }
+        Ok(())
+    }
+
     pub fn programming_quiz() -> Self {
         QuizConfig {
             quiz_type: "programming".to_string(),
-            passing_score: 0.6,
+            scoring: ScoringPolicy::standard(0.6),
             questions: vec![
                 Question {
                     id: 1,
@@ -35,21 +86,21 @@ impl QuizConfig {
                     id: 2,
                     question: "Which language is best for FHE?".to_string(),
                     options: vec![
-                        "Rust".to_string(),
                         "Python".to_string(),
+                        "Rust".to_string(),
                         "JavaScript".to_string(),
                     ],
-                    correct_answer: 0,
+                    correct_answer: 1,
                 },
                 Question {
                     id: 3,
                     question: "What is Zero-Knowledge Proof?".to_string(),
                     options: vec![
-                        "Proving something without revealing details".to_string(),
                         "A type of encryption".to_string(),
                         "A blockchain consensus".to_string(),
+                        "Proving something without revealing details".to_string(),
                     ],
-                    correct_answer: 0,
+                    correct_answer: 2,
                 },
             ],
         }
@@ -58,7 +109,7 @@ impl QuizConfig {
     pub fn math_quiz() -> Self {
         QuizConfig {
             quiz_type: "math".to_string(),
-            passing_score: 0.7,
+            scoring: ScoringPolicy::standard(0.7),
             questions: vec![
                 Question {
                     id: 1,
@@ -69,14 +120,14 @@ impl QuizConfig {
                 Question {
                     id: 2,
                     question: "Solve: 8 × 7".to_string(),
-                    options: vec!["56".to_string(), "54".to_string(), "64".to_string()],
-                    correct_answer: 0,
+                    options: vec!["54".to_string(), "56".to_string(), "64".to_string()],
+                    correct_answer: 1,
                 },
                 Question {
                     id: 3,
                     question: "What is 144 ÷ 12?".to_string(),
-                    options: vec!["12".to_string(), "11".to_string(), "13".to_string()],
-                    correct_answer: 0,
+                    options: vec!["11".to_string(), "13".to_string(), "12".to_string()],
+                    correct_answer: 2,
                 },
             ],
         }
@@ -85,7 +136,7 @@ impl QuizConfig {
     pub fn blockchain_quiz() -> Self {
         QuizConfig {
             quiz_type: "blockchain".to_string(),
-            passing_score: 0.6,
+            scoring: ScoringPolicy::standard(0.6),
             questions: vec![
                 Question {
                     id: 1,
@@ -101,21 +152,21 @@ impl QuizConfig {
                     id: 2,
                     question: "Which consensus mechanism does Ethereum use?".to_string(),
                     options: vec![
-                        "Proof of Stake".to_string(),
                         "Proof of Work".to_string(),
+                        "Proof of Stake".to_string(),
                         "Delegated Proof of Stake".to_string(),
                     ],
-                    correct_answer: 0,
+                    correct_answer: 1,
                 },
                 Question {
                     id: 3,
                     question: "What is gas fee in Ethereum?".to_string(),
                     options: vec![
-                        "Transaction execution cost".to_string(),
                         "Mining reward".to_string(),
                         "Network subscription".to_string(),
+                        "Transaction execution cost".to_string(),
                     ],
-                    correct_answer: 0,
+                    correct_answer: 2,
                 },
             ],
         }
@@ -124,7 +175,7 @@ impl QuizConfig {
     pub fn security_quiz() -> Self {
         QuizConfig {
             quiz_type: "security".to_string(),
-            passing_score: 0.8,
+            scoring: ScoringPolicy::standard(0.8),
             questions: vec![
                 Question {
                     id: 1,
@@ -140,21 +191,21 @@ impl QuizConfig {
                     id: 2,
                     question: "What is 2FA?".to_string(),
                     options: vec![
-                        "Two-Factor Authentication".to_string(),
                         "Two-File Archive".to_string(),
+                        "Two-Factor Authentication".to_string(),
                         "Two-Function Algorithm".to_string(),
                     ],
-                    correct_answer: 0,
+                    correct_answer: 1,
                 },
                 Question {
                     id: 3,
                     question: "What's a common password best practice?".to_string(),
                     options: vec![
-                        "Use long, complex passwords".to_string(),
                         "Use same password everywhere".to_string(),
                         "Use personal information".to_string(),
+                        "Use long, complex passwords".to_string(),
                     ],
-                    correct_answer: 0,
+                    correct_answer: 2,
                 },
             ],
         }
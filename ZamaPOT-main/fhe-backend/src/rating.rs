@@ -0,0 +1,141 @@
+use serde::{Deserialize, Serialize};
+use std::f64::consts::{LN_10, PI};
+use crate::irt;
+
+// Glicko-1 defaults
+pub const INITIAL_RATING: f64 = 1500.0;
+pub const INITIAL_DEVIATION: f64 = 350.0;
+const MIN_DEVIATION: f64 = 30.0;
+// Deviation regained per idle day, so stale ratings become uncertain again
+const DAILY_DEVIATION_GROWTH: f64 = 10.0;
+const Q: f64 = LN_10 / 400.0;
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+pub struct SkillRating {
+    pub rating: f64,
+    pub deviation: f64,
+    pub attempts: u32,
+    pub last_updated: u64, // unix seconds
+}
+
+/// One answered item, treated as a game against an opponent rated by difficulty.
+#[derive(Debug, Clone, Copy)]
+pub struct RatedItem {
+    pub difficulty: f32,
+    pub score: f32, // 0..1
+}
+
+impl Default for SkillRating {
+    fn default() -> Self {
+        SkillRating {
+            rating: INITIAL_RATING,
+            deviation: INITIAL_DEVIATION,
+            attempts: 0,
+            last_updated: 0,
+        }
+    }
+}
+
+/// Converts between Glicko ratings and IRT logits: both are logistic, so a
+/// logit is Q rating points scaled, centered on the initial rating.
+pub fn rating_to_logit(rating: f64) -> f64 {
+    (rating - INITIAL_RATING) * Q
+}
+
+pub fn logit_to_rating(logit: f64) -> f64 {
+    INITIAL_RATING + logit / Q
+}
+
+fn g(deviation: f64) -> f64 {
+    1.0 / (1.0 + 3.0 * Q.powi(2) * deviation.powi(2) / PI.powi(2)).sqrt()
+}
+
+impl SkillRating {
+    /// Deviation after `now - last_updated` idle time.
+    pub fn current_deviation(&self, now: u64) -> f64 {
+        if self.attempts == 0 {
+            return self.deviation;
+        }
+        let idle_days = now.saturating_sub(self.last_updated) as f64 / 86_400.0;
+        (self.deviation.powi(2) + DAILY_DEVIATION_GROWTH.powi(2) * idle_days)
+            .sqrt()
+            .min(INITIAL_DEVIATION)
+    }
+
+    /// Glicko-1 update treating one attempt as a rating period. Item
+    /// opponents have exact ratings (zero deviation) derived from difficulty.
+    pub fn update(&mut self, items: &[RatedItem], now: u64) {
+        if items.is_empty() {
+            return;
+        }
+        let deviation = self.current_deviation(now);
+
+        let mut d_inverse = 0.0;
+        let mut delta_sum = 0.0;
+        for item in items {
+            let opponent = logit_to_rating(irt::logit_difficulty(item.difficulty));
+            let g_opponent = g(0.0);
+            let expected = 1.0 / (1.0 + 10f64.powf(-g_opponent * (self.rating - opponent) / 400.0));
+            d_inverse += Q.powi(2) * g_opponent.powi(2) * expected * (1.0 - expected);
+            delta_sum += g_opponent * (item.score.clamp(0.0, 1.0) as f64 - expected);
+        }
+
+        let precision = 1.0 / deviation.powi(2) + d_inverse;
+        self.rating += Q / precision * delta_sum;
+        self.deviation = (1.0 / precision).sqrt().max(MIN_DEVIATION);
+        self.attempts += 1;
+        self.last_updated = now;
+    }
+
+    /// Item difficulty (0..1) at which the candidate has even odds.
+    pub fn matched_difficulty(&self) -> f32 {
+        irt::unit_difficulty(rating_to_logit(self.rating))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn items(score: f32) -> Vec<RatedItem> {
+        vec![RatedItem { difficulty: 0.5, score }; 5]
+    }
+
+    #[test]
+    fn ratings_and_logits_share_a_scale() {
+        assert_eq!(rating_to_logit(INITIAL_RATING), 0.0);
+        assert!((rating_to_logit(logit_to_rating(1.5)) - 1.5).abs() < 1e-9);
+        assert_eq!(SkillRating::default().matched_difficulty(), 0.5);
+    }
+
+    #[test]
+    fn results_move_the_rating_and_shrink_the_deviation() {
+        let mut strong = SkillRating::default();
+        strong.update(&items(1.0), 1_000);
+        let mut weak = SkillRating::default();
+        weak.update(&items(0.0), 1_000);
+
+        assert!(strong.rating > INITIAL_RATING && weak.rating < INITIAL_RATING);
+        assert!((strong.rating - INITIAL_RATING - (INITIAL_RATING - weak.rating)).abs() < 1e-6);
+        assert!(strong.deviation < INITIAL_DEVIATION);
+        assert!(strong.matched_difficulty() > 0.5);
+        assert_eq!((strong.attempts, strong.last_updated), (1, 1_000));
+
+        // An empty attempt changes nothing
+        let before = strong;
+        strong.update(&[], 2_000);
+        assert_eq!(strong, before);
+    }
+
+    #[test]
+    fn idle_time_restores_uncertainty() {
+        let mut rating = SkillRating::default();
+        for day in 0..20 {
+            rating.update(&items(0.6), day * 86_400);
+        }
+        let settled = rating.current_deviation(19 * 86_400);
+        assert!((MIN_DEVIATION..100.0).contains(&settled), "{}", settled);
+        assert!(rating.current_deviation(119 * 86_400) > settled);
+        assert_eq!(rating.current_deviation(u64::MAX), INITIAL_DEVIATION);
+    }
+}
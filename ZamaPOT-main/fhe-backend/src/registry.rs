@@ -0,0 +1,465 @@
+use ed25519_dalek::VerifyingKey;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+use std::io::{BufRead, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use crate::certificates::{self, CertificateError, IssuerKey, SignedCertificate};
+use crate::disclosure::{CommitmentBatch, SelectiveCertificate};
+
+pub const REVOCATION_LIST_VERSION: u8 = 1;
+
+/// An issued certificate as stored by the registry.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct RegistryEntry {
+    pub certificate: SignedCertificate,
+    pub passed: bool,
+    #[serde(default)]
+    pub revocation: Option<Revocation>,
+    #[serde(default)]
+    pub token_id: Option<u64>, // passport token minted for it, if relayed
+    #[serde(default)]
+    pub selective: Option<SelectiveCertificate>, // attribute commitments and their openings
+    #[serde(default)]
+    pub holder_secret_hash: Option<String>, // sha256 hex of the secret that authorizes presentations
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct Revocation {
+    pub revoked_at: u64, // unix seconds
+    pub reason: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct RevokedCertificate {
+    pub certificate_id: String,
+    pub revoked_at: u64,
+    pub reason: String,
+}
+
+/// Every revocation as of `issued_at`, sorted by certificate id.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct RevocationList {
+    pub version: u8,
+    pub issued_at: u64,
+    pub revocations: Vec<RevokedCertificate>,
+}
+
+/// A revocation list snapshot offline verifiers can check against the issuer key.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct SignedRevocationList {
+    pub list: RevocationList,
+    pub key_id: String,
+    pub signature: String, // hex
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum RevocationError {
+    UnknownCertificate(String),
+    AlreadyRevoked(String),
+    Storage(String),
+}
+
+impl fmt::Display for RevocationError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RevocationError::UnknownCertificate(id) => write!(f, "certificate {} is not in the registry", id),
+            RevocationError::AlreadyRevoked(id) => write!(f, "certificate {} is already revoked", id),
+            RevocationError::Storage(e) => write!(f, "failed to record revocation: {}", e),
+        }
+    }
+}
+
+/// What a verifier learns about a certificate. Deliberately omits the score.
+#[derive(Debug, Serialize, Clone)]
+pub struct CertificateStatus {
+    pub certificate_id: String,
+    pub valid: bool,
+    pub reason: Option<String>, // why it is not valid
+    pub issued_at: Option<u64>,
+    pub skill: Option<String>,
+    pub level: Option<u8>,
+    pub expires_at: Option<u64>,
+    pub revoked: bool,
+    pub revocation: Option<Revocation>,
+    pub token_id: Option<u64>,
+}
+
+/// Issued certificates by id, appended as JSON lines to `path` when set.
+#[derive(Debug, Default)]
+pub struct CertificateRegistry {
+    entries: Mutex<HashMap<String, RegistryEntry>>,
+    path: Option<PathBuf>,
+}
+
+impl CertificateStatus {
+    fn unknown(certificate_id: &str) -> Self {
+        CertificateStatus {
+            certificate_id: certificate_id.to_string(),
+            valid: false,
+            reason: Some("certificate is not in the registry".to_string()),
+            issued_at: None,
+            skill: None,
+            level: None,
+            expires_at: None,
+            revoked: false,
+            revocation: None,
+            token_id: None,
+        }
+    }
+}
+
+impl RegistryEntry {
+    fn status(&self, reason: Option<String>, now: u64) -> CertificateStatus {
+        let payload = &self.certificate.payload;
+        let expired = payload.expires_at.is_some_and(|expires_at| now >= expires_at);
+        let reason = reason
+            .or_else(|| self.revocation.as_ref().map(|r| format!("certificate has been revoked: {}", r.reason)))
+            .or_else(|| expired.then(|| "certificate has expired".to_string()))
+            .or_else(|| (!self.passed).then(|| "assessment was not passed".to_string()));
+        CertificateStatus {
+            certificate_id: payload.certificate_id.clone(),
+            valid: reason.is_none(),
+            reason,
+            issued_at: Some(payload.issued_at),
+            skill: Some(payload.quiz_type.clone()),
+            level: Some(payload.level),
+            expires_at: payload.expires_at,
+            revoked: self.revocation.is_some(),
+            revocation: self.revocation.clone(),
+            token_id: self.token_id,
+        }
+    }
+}
+
+impl CertificateRegistry {
+    /// Opens a registry file, replaying previously issued certificates.
+    /// Later lines for the same id (revocations, relayed tokens) replace
+    /// earlier ones.
+    pub fn open(path: &Path) -> std::io::Result<Self> {
+        let mut entries: HashMap<String, RegistryEntry> = HashMap::new();
+        if path.exists() {
+            let file = std::fs::File::open(path)?;
+            for line in std::io::BufReader::new(file).lines() {
+                let line = line?;
+                if line.trim().is_empty() {
+                    continue;
+                }
+                let entry: RegistryEntry = serde_json::from_str(&line)
+                    .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?;
+                entries.insert(entry.certificate.payload.certificate_id.clone(), entry);
+            }
+        }
+        Ok(CertificateRegistry {
+            entries: Mutex::new(entries),
+            path: Some(path.to_path_buf()),
+        })
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.lock().unwrap().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn persist(&self, entry: &RegistryEntry) -> std::io::Result<()> {
+        let Some(path) = &self.path else { return Ok(()) };
+        let line = serde_json::to_string(entry)
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?;
+        let mut file = std::fs::OpenOptions::new().create(true).append(true).open(path)?;
+        writeln!(file, "{}", line)
+    }
+
+    /// Records a newly issued certificate. Ids are never reused, so an
+    /// existing entry, and any revocation on it, is never replaced.
+    pub fn register(
+        &self,
+        certificate: SignedCertificate,
+        selective: SelectiveCertificate,
+        holder_secret: &str,
+        passed: bool,
+    ) -> std::io::Result<()> {
+        let mut entries = self.entries.lock().unwrap();
+        let certificate_id = certificate.payload.certificate_id.clone();
+        if entries.contains_key(&certificate_id) {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::AlreadyExists,
+                format!("certificate {} is already in the registry", certificate_id),
+            ));
+        }
+        let entry = RegistryEntry {
+            certificate,
+            passed,
+            revocation: None,
+            token_id: None,
+            selective: Some(selective),
+            holder_secret_hash: Some(certificates::sha256_hex(holder_secret.as_bytes())),
+        };
+        self.persist(&entry)?;
+        entries.insert(certificate_id, entry);
+        Ok(())
+    }
+
+    /// Whether `secret` is the one handed to the certificate's holder.
+    pub fn is_holder(&self, certificate_id: &str, secret: &str) -> bool {
+        // Comparing digests keeps the comparison time independent of the secret
+        let digest = certificates::sha256_hex(secret.as_bytes());
+        self.entries.lock().unwrap().get(certificate_id)
+            .and_then(|entry| entry.holder_secret_hash.as_ref())
+            .is_some_and(|expected| *expected == digest)
+    }
+
+    pub fn entry(&self, certificate_id: &str) -> Option<RegistryEntry> {
+        self.entries.lock().unwrap().get(certificate_id).cloned()
+    }
+
+    /// Takes an unused commitment batch for one presentation, see
+    /// `SelectiveCertificate::take_batch`. None if the certificate was
+    /// issued without commitments.
+    pub fn take_batch(&self, certificate_id: &str, key: &IssuerKey) -> std::io::Result<Option<CommitmentBatch>> {
+        let mut entries = self.entries.lock().unwrap();
+        let Some(entry) = entries.get_mut(certificate_id) else { return Ok(None) };
+        let mut presented = entry.clone();
+        let payload = presented.certificate.payload.clone();
+        let Some(selective) = presented.selective.as_mut() else { return Ok(None) };
+        let batch = selective.take_batch(key, &payload);
+        self.persist(&presented)?;
+        *entry = presented;
+        Ok(Some(batch))
+    }
+
+    /// Stores the passport token minted for a certificate.
+    pub fn record_token(&self, certificate_id: &str, token_id: u64) -> std::io::Result<()> {
+        let mut entries = self.entries.lock().unwrap();
+        let Some(entry) = entries.get_mut(certificate_id) else {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::NotFound,
+                format!("certificate {} is not in the registry", certificate_id),
+            ));
+        };
+        let mut minted = entry.clone();
+        minted.token_id = Some(token_id);
+        self.persist(&minted)?;
+        *entry = minted;
+        Ok(())
+    }
+
+    /// Status of an issued certificate. The stored signature is re-checked,
+    /// so records signed by a retired key no longer verify.
+    pub fn lookup(&self, key: &VerifyingKey, certificate_id: &str, now: u64) -> CertificateStatus {
+        match self.entries.lock().unwrap().get(certificate_id) {
+            Some(entry) => {
+                let reason = certificates::verify(key, &entry.certificate).err().map(|e| e.to_string());
+                entry.status(reason, now)
+            }
+            None => CertificateStatus::unknown(certificate_id),
+        }
+    }
+
+    /// Status of the certificate behind a selective-disclosure handle.
+    pub fn lookup_handle(&self, key: &VerifyingKey, handle: &str, now: u64) -> Option<CertificateStatus> {
+        self.entries.lock().unwrap().values()
+            .find(|entry| entry.selective.as_ref()
+                .is_some_and(|s| s.batches.iter().any(|batch| batch.commitments.handle == handle)))
+            .map(|entry| {
+                let reason = certificates::verify(key, &entry.certificate).err().map(|e| e.to_string());
+                entry.status(reason, now)
+            })
+    }
+
+    /// Verifies a presented certificate: the signature must check out and
+    /// it must match what the registry issued under that id.
+    pub fn verify(&self, key: &VerifyingKey, certificate: &SignedCertificate, now: u64) -> CertificateStatus {
+        let certificate_id = &certificate.payload.certificate_id;
+        let entries = self.entries.lock().unwrap();
+        let Some(entry) = entries.get(certificate_id) else {
+            return CertificateStatus::unknown(certificate_id);
+        };
+
+        let reason = match certificates::verify(key, certificate) {
+            Err(e) => Some(e.to_string()),
+            Ok(()) if entry.certificate != *certificate => {
+                Some("certificate does not match the issued record".to_string())
+            }
+            Ok(()) => None,
+        };
+        entry.status(reason, now)
+    }
+
+    /// Revokes an issued certificate. Revocations are permanent.
+    pub fn revoke(&self, certificate_id: &str, reason: String, now: u64) -> Result<Revocation, RevocationError> {
+        let mut entries = self.entries.lock().unwrap();
+        let entry = entries.get_mut(certificate_id)
+            .ok_or_else(|| RevocationError::UnknownCertificate(certificate_id.to_string()))?;
+        if entry.revocation.is_some() {
+            return Err(RevocationError::AlreadyRevoked(certificate_id.to_string()));
+        }
+
+        let mut revoked = entry.clone();
+        revoked.revocation = Some(Revocation { revoked_at: now, reason });
+        self.persist(&revoked).map_err(|e| RevocationError::Storage(e.to_string()))?;
+        *entry = revoked;
+        Ok(entry.revocation.clone().unwrap())
+    }
+
+    pub fn revocation_list(&self, now: u64) -> RevocationList {
+        let mut revocations: Vec<RevokedCertificate> = self.entries.lock().unwrap().iter()
+            .filter_map(|(certificate_id, entry)| {
+                entry.revocation.as_ref().map(|revocation| RevokedCertificate {
+                    certificate_id: certificate_id.clone(),
+                    revoked_at: revocation.revoked_at,
+                    reason: revocation.reason.clone(),
+                })
+            })
+            .collect();
+        revocations.sort_by(|a, b| a.certificate_id.cmp(&b.certificate_id));
+        RevocationList {
+            version: REVOCATION_LIST_VERSION,
+            issued_at: now,
+            revocations,
+        }
+    }
+}
+
+impl RevocationList {
+    /// Header line, then one tab-separated line per revocation.
+    pub fn canonical_bytes(&self) -> Vec<u8> {
+        let mut text = format!("ppot-revocations-v{}\n{}\n{}", self.version, self.issued_at, self.revocations.len());
+        for revocation in &self.revocations {
+            text.push_str(&format!(
+                "\n{}\t{}\t{}",
+                certificates::escape_field(&revocation.certificate_id),
+                revocation.revoked_at,
+                certificates::escape_field(&revocation.reason),
+            ));
+        }
+        text.into_bytes()
+    }
+
+    pub fn sign(self, key: &IssuerKey) -> SignedRevocationList {
+        SignedRevocationList {
+            signature: key.sign_bytes(&self.canonical_bytes()),
+            key_id: key.key_id(),
+            list: self,
+        }
+    }
+
+    pub fn is_revoked(&self, certificate_id: &str) -> bool {
+        self.revocations.iter().any(|r| r.certificate_id == certificate_id)
+    }
+}
+
+/// Checks a revocation list snapshot against an issuer public key.
+pub fn verify_revocation_list(key: &VerifyingKey, snapshot: &SignedRevocationList) -> Result<(), CertificateError> {
+    certificates::verify_bytes(key, &snapshot.key_id, &snapshot.list.canonical_bytes(), &snapshot.signature)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::certificates::tests::payload;
+    use crate::disclosure;
+
+    const NOW: u64 = 1_750_000_000;
+
+    fn register(registry: &CertificateRegistry, key: &IssuerKey, certificate_id: &str) -> SignedCertificate {
+        let mut payload = payload();
+        payload.certificate_id = certificate_id.to_string();
+        let certificate = key.sign(payload);
+        let selective = disclosure::commit(key, &certificate.payload);
+        registry.register(certificate.clone(), selective, "holder-secret", true).unwrap();
+        certificate
+    }
+
+    #[test]
+    fn ids_are_never_reused() {
+        let key = IssuerKey::generate();
+        let registry = CertificateRegistry::default();
+        let certificate = register(&registry, &key, "CERT_a");
+        let selective = disclosure::commit(&key, &certificate.payload);
+        let error = registry.register(certificate, selective, "other-secret", false).unwrap_err();
+        assert_eq!(error.kind(), std::io::ErrorKind::AlreadyExists);
+        assert!(registry.is_holder("CERT_a", "holder-secret"));
+        assert!(!registry.is_holder("CERT_a", "other-secret"));
+    }
+
+    #[test]
+    fn status_reflects_revocation_and_tampering() {
+        let key = IssuerKey::generate();
+        let registry = CertificateRegistry::default();
+        let certificate = register(&registry, &key, "CERT_a");
+        assert!(registry.lookup(&key.verifying_key(), "CERT_a", NOW).valid);
+        assert!(registry.verify(&key.verifying_key(), &certificate, NOW).valid);
+        assert!(!registry.lookup(&key.verifying_key(), "CERT_a", 1_800_000_000).valid, "expired");
+        assert!(!registry.lookup(&key.verifying_key(), "CERT_b", NOW).valid);
+
+        // Re-signed with a higher level: the signature checks out but the record doesn't match
+        let mut raised = certificate.payload.clone();
+        raised.level = 5;
+        let status = registry.verify(&key.verifying_key(), &key.sign(raised), NOW);
+        assert_eq!(status.reason.as_deref(), Some("certificate does not match the issued record"));
+
+        registry.revoke("CERT_a", "leaked answers".to_string(), NOW).unwrap();
+        let status = registry.lookup(&key.verifying_key(), "CERT_a", NOW);
+        assert!(!status.valid && status.revoked);
+        assert_eq!(
+            registry.revoke("CERT_a", "again".to_string(), NOW),
+            Err(RevocationError::AlreadyRevoked("CERT_a".to_string())),
+        );
+        assert_eq!(
+            registry.revoke("CERT_b", "unknown".to_string(), NOW),
+            Err(RevocationError::UnknownCertificate("CERT_b".to_string())),
+        );
+    }
+
+    #[test]
+    fn revocation_lists_are_signed_and_sorted() {
+        let key = IssuerKey::generate();
+        let registry = CertificateRegistry::default();
+        for id in ["CERT_c", "CERT_a", "CERT_b"] {
+            register(&registry, &key, id);
+        }
+        registry.revoke("CERT_c", "test".to_string(), NOW).unwrap();
+        registry.revoke("CERT_a", "test".to_string(), NOW).unwrap();
+
+        let snapshot = registry.revocation_list(NOW).sign(&key);
+        let ids: Vec<&str> = snapshot.list.revocations.iter().map(|r| r.certificate_id.as_str()).collect();
+        assert_eq!(ids, ["CERT_a", "CERT_c"]);
+        assert!(snapshot.list.is_revoked("CERT_c") && !snapshot.list.is_revoked("CERT_b"));
+        assert_eq!(verify_revocation_list(&key.verifying_key(), &snapshot), Ok(()));
+
+        let mut trimmed = snapshot;
+        trimmed.list.revocations.pop();
+        assert_eq!(
+            verify_revocation_list(&key.verifying_key(), &trimmed),
+            Err(CertificateError::InvalidSignature),
+        );
+    }
+
+    #[test]
+    fn reopening_replays_later_lines() {
+        let key = IssuerKey::generate();
+        let path = std::env::temp_dir().join(format!("ppot-registry-{}.jsonl", key.key_id()));
+        let handle = {
+            let registry = CertificateRegistry::open(&path).unwrap();
+            register(&registry, &key, "CERT_a");
+            register(&registry, &key, "CERT_b");
+            registry.record_token("CERT_a", 7).unwrap();
+            registry.revoke("CERT_b", "test".to_string(), NOW).unwrap();
+            registry.take_batch("CERT_a", &key).unwrap().unwrap().commitments.handle
+        };
+
+        let reopened = CertificateRegistry::open(&path).unwrap();
+        assert_eq!(reopened.len(), 2);
+        let entry = reopened.entry("CERT_a").unwrap();
+        assert_eq!(entry.token_id, Some(7));
+        assert_eq!(entry.selective.unwrap().presented, 1);
+        assert!(reopened.lookup_handle(&key.verifying_key(), &handle, NOW).unwrap().valid);
+        assert!(reopened.entry("CERT_b").unwrap().revocation.is_some());
+        assert!(reopened.is_holder("CERT_b", "holder-secret"));
+        std::fs::remove_file(&path).unwrap();
+    }
+}
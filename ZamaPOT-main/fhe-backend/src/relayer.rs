@@ -0,0 +1,353 @@
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::sync::mpsc;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use crate::attestation::{self, MintAttestation};
+use crate::registry::CertificateRegistry;
+use crate::rpc::{self, RpcClient, RpcError};
+
+// EnhancedTalentPassport.MINT_FEE, 0.001 ether
+pub const DEFAULT_MINT_FEE_WEI: u128 = 1_000_000_000_000_000;
+const RECEIPT_POLL_INTERVAL: Duration = Duration::from_secs(1);
+const RECEIPT_TIMEOUT: Duration = Duration::from_secs(120);
+
+/// Sends transactions from an account the node manages (`eth_sendTransaction`),
+/// as Hardhat and Anvil do for their dev accounts.
+#[derive(Debug, Clone)]
+pub struct RelayerConfig {
+    pub rpc_url: String,
+    pub from: [u8; 20],
+    pub contract: [u8; 20],
+    pub mint_fee_wei: u128,
+}
+
+#[derive(Debug, Serialize, Clone, PartialEq)]
+#[serde(tag = "state", rename_all = "snake_case")]
+pub enum RelayStatus {
+    Queued,
+    Minting { tx_hash: String },
+    Transferring { token_id: u64, mint_tx: String, tx_hash: String },
+    Completed { token_id: u64, mint_tx: String, transfer_tx: String },
+    /// Minted but still held by the relayer; the transfer can be retried.
+    TransferFailed { token_id: u64, mint_tx: String, error: String },
+    Failed { error: String },
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct RelayJob {
+    pub certificate_id: String,
+    pub wallet: String,
+    pub status: RelayStatus,
+}
+
+enum RelayStep {
+    Mint(Box<MintAttestation>),
+    Transfer { token_id: u64, mint_tx: String }, // retry after a failed transfer
+}
+
+struct RelayRequest {
+    certificate_id: String,
+    wallet: [u8; 20],
+    step: RelayStep,
+}
+
+/// Mints badges on behalf of candidates and transfers them to their wallets.
+/// Jobs run one at a time on a background thread.
+///
+/// `mintTalentBadge` mints to `msg.sender`, so the contract records the
+/// relayer account as the minter: `BadgeMinted.user`, `userBadges` and
+/// `getUserBadges` name the relayer, never the candidate. Only ERC-721
+/// ownership moves to the wallet, so holders are found with `ownerOf` and
+/// `Transfer` events. The mint consumes the score in `usedScores`; if the
+/// transfer then fails, the token stays with the relayer until `retry`
+/// sends it again.
+pub struct Relayer {
+    jobs: Arc<Mutex<HashMap<String, RelayJob>>>,
+    queue: Mutex<mpsc::Sender<RelayRequest>>,
+}
+
+impl Relayer {
+    pub fn start(config: RelayerConfig, registry: Arc<CertificateRegistry>) -> Self {
+        let jobs: Arc<Mutex<HashMap<String, RelayJob>>> = Arc::new(Mutex::new(HashMap::new()));
+        let (sender, receiver) = mpsc::channel::<RelayRequest>();
+
+        let worker_jobs = jobs.clone();
+        std::thread::spawn(move || {
+            let client = RpcClient::new(&config.rpc_url);
+            for request in receiver {
+                let certificate_id = request.certificate_id.clone();
+                let set_status = |status: RelayStatus| {
+                    if let Some(job) = worker_jobs.lock().unwrap().get_mut(&certificate_id) {
+                        job.status = status;
+                    }
+                };
+
+                let (token_id, mint_tx) = match request.step {
+                    RelayStep::Mint(attestation) => match mint(&client, &config, &attestation, &set_status) {
+                        Ok((token_id, mint_tx)) => {
+                            println!("⛓️  Minted token {} for certificate {}", token_id, certificate_id);
+                            if let Err(e) = registry.record_token(&certificate_id, token_id) {
+                                println!("⚠️  Failed to record token for {}: {}", certificate_id, e);
+                            }
+                            (token_id, mint_tx)
+                        }
+                        Err(e) => {
+                            println!("⚠️  Relaying certificate {} failed: {}", certificate_id, e);
+                            set_status(RelayStatus::Failed { error: e.to_string() });
+                            continue;
+                        }
+                    },
+                    RelayStep::Transfer { token_id, mint_tx } => (token_id, mint_tx),
+                };
+
+                match transfer(&client, &config, &request.wallet, token_id, &mint_tx, &set_status) {
+                    Ok(transfer_tx) => set_status(RelayStatus::Completed { token_id, mint_tx, transfer_tx }),
+                    Err(e) => {
+                        println!("⚠️  Transferring token {} for {} failed: {}", token_id, certificate_id, e);
+                        set_status(RelayStatus::TransferFailed { token_id, mint_tx, error: e.to_string() });
+                    }
+                }
+            }
+        });
+
+        Relayer {
+            jobs,
+            queue: Mutex::new(sender),
+        }
+    }
+
+    pub fn submit(&self, attestation: MintAttestation, wallet: [u8; 20]) -> RelayStatus {
+        let job = RelayJob {
+            certificate_id: attestation.args.certificate_id.clone(),
+            wallet: attestation::to_hex(&wallet),
+            status: RelayStatus::Queued,
+        };
+        self.jobs.lock().unwrap().insert(job.certificate_id.clone(), job);
+
+        self.enqueue(RelayRequest {
+            certificate_id: attestation.args.certificate_id.clone(),
+            wallet,
+            step: RelayStep::Mint(Box::new(attestation)),
+        })
+    }
+
+    /// Queues the transfer again for a job stuck in `TransferFailed`. The
+    /// badge is never minted twice. None if there is no such job.
+    pub fn retry(&self, certificate_id: &str) -> Option<Result<RelayStatus, RelayStatus>> {
+        let mut jobs = self.jobs.lock().unwrap();
+        let job = jobs.get_mut(certificate_id)?;
+        let RelayStatus::TransferFailed { token_id, mint_tx, .. } = &job.status else {
+            return Some(Err(job.status.clone()));
+        };
+        let Some(wallet) = attestation::parse_address(&job.wallet) else {
+            return Some(Err(job.status.clone()));
+        };
+        let request = RelayRequest {
+            certificate_id: certificate_id.to_string(),
+            wallet,
+            step: RelayStep::Transfer { token_id: *token_id, mint_tx: mint_tx.clone() },
+        };
+        job.status = RelayStatus::Queued;
+        drop(jobs);
+        Some(Ok(self.enqueue(request)))
+    }
+
+    fn enqueue(&self, request: RelayRequest) -> RelayStatus {
+        let certificate_id = request.certificate_id.clone();
+        if self.queue.lock().unwrap().send(request).is_err() {
+            let status = RelayStatus::Failed { error: "relayer is not running".to_string() };
+            if let Some(job) = self.jobs.lock().unwrap().get_mut(&certificate_id) {
+                job.status = status.clone();
+            }
+            return status;
+        }
+        RelayStatus::Queued
+    }
+
+    pub fn job(&self, certificate_id: &str) -> Option<RelayJob> {
+        self.jobs.lock().unwrap().get(certificate_id).cloned()
+    }
+}
+
+fn send_transaction(client: &RpcClient, config: &RelayerConfig, data: &[u8], value: u128) -> Result<String, RpcError> {
+    let tx = json!({
+        "from": attestation::to_hex(&config.from),
+        "to": attestation::to_hex(&config.contract),
+        "value": rpc::quantity(value),
+        "data": attestation::to_hex(data),
+    });
+    let hash = client.call("eth_sendTransaction", json!([tx]))?;
+    hash.as_str().map(str::to_string)
+        .ok_or_else(|| RpcError::UnexpectedResponse(format!("expected a tx hash, got {}", hash)))
+}
+
+/// Polls until the transaction is mined; reverted transactions are errors.
+fn wait_for_receipt(client: &RpcClient, tx_hash: &str) -> Result<Value, RpcError> {
+    let started = Instant::now();
+    loop {
+        let receipt = client.call("eth_getTransactionReceipt", json!([tx_hash]))?;
+        if !receipt.is_null() {
+            if receipt["status"] != "0x1" {
+                return Err(RpcError::UnexpectedResponse(format!("transaction {} reverted", tx_hash)));
+            }
+            return Ok(receipt);
+        }
+        if started.elapsed() > RECEIPT_TIMEOUT {
+            return Err(RpcError::UnexpectedResponse(format!("transaction {} not mined in time", tx_hash)));
+        }
+        std::thread::sleep(RECEIPT_POLL_INTERVAL);
+    }
+}
+
+/// Token id from the receipt's BadgeMinted log: the first non-indexed word.
+fn minted_token_id(receipt: &Value, contract: &[u8; 20]) -> Result<u64, RpcError> {
+    let contract = attestation::to_hex(contract);
+    let topic = attestation::badge_minted_topic();
+    receipt["logs"].as_array().into_iter().flatten()
+        .filter(|log| log["address"].as_str().is_some_and(|a| a.eq_ignore_ascii_case(&contract)))
+        .filter(|log| log["topics"][0].as_str().is_some_and(|t| t.eq_ignore_ascii_case(&topic)))
+        .find_map(|log| {
+            let data = log["data"].as_str()?.strip_prefix("0x")?;
+            let word = data.get(..64)?;
+            // Token ids are sequential, so the high bytes are zero
+            u64::from_str_radix(&word[48..], 16).ok()
+        })
+        .ok_or_else(|| RpcError::UnexpectedResponse("mint receipt has no BadgeMinted log".to_string()))
+}
+
+/// Mints the attested badge to the relayer account.
+fn mint(
+    client: &RpcClient,
+    config: &RelayerConfig,
+    attestation: &MintAttestation,
+    set_status: &dyn Fn(RelayStatus),
+) -> Result<(u64, String), RpcError> {
+    let calldata = hex::decode(attestation.calldata.trim_start_matches("0x"))
+        .map_err(|e| RpcError::UnexpectedResponse(e.to_string()))?;
+
+    let mint_tx = send_transaction(client, config, &calldata, config.mint_fee_wei)?;
+    set_status(RelayStatus::Minting { tx_hash: mint_tx.clone() });
+    let receipt = wait_for_receipt(client, &mint_tx)?;
+    let token_id = minted_token_id(&receipt, &config.contract)?;
+    Ok((token_id, mint_tx))
+}
+
+/// The contract mints to msg.sender, so the badge is handed over afterwards.
+fn transfer(
+    client: &RpcClient,
+    config: &RelayerConfig,
+    wallet: &[u8; 20],
+    token_id: u64,
+    mint_tx: &str,
+    set_status: &dyn Fn(RelayStatus),
+) -> Result<String, RpcError> {
+    let transfer = attestation::transfer_calldata(&config.from, wallet, token_id);
+    let transfer_tx = send_transaction(client, config, &transfer, 0)?;
+    set_status(RelayStatus::Transferring { token_id, mint_tx: mint_tx.to_string(), tx_hash: transfer_tx.clone() });
+    wait_for_receipt(client, &transfer_tx)?;
+    Ok(transfer_tx)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CONTRACT: [u8; 20] = [0xab; 20];
+    const WALLET: [u8; 20] = [0x11; 20];
+
+    fn badge_log(address: &[u8; 20], token_id: u64) -> Value {
+        json!({
+            "address": attestation::to_hex(address),
+            "topics": [attestation::badge_minted_topic(), format!("0x{}", "0".repeat(64))],
+            "data": format!("0x{:064x}{}", token_id, "0".repeat(64)),
+        })
+    }
+
+    fn idle_relayer() -> (Relayer, mpsc::Receiver<RelayRequest>) {
+        let (sender, receiver) = mpsc::channel();
+        let relayer = Relayer {
+            jobs: Arc::new(Mutex::new(HashMap::new())),
+            queue: Mutex::new(sender),
+        };
+        (relayer, receiver)
+    }
+
+    fn insert_job(relayer: &Relayer, status: RelayStatus) {
+        let job = RelayJob {
+            certificate_id: "CERT_1".to_string(),
+            wallet: attestation::to_hex(&WALLET),
+            status,
+        };
+        relayer.jobs.lock().unwrap().insert(job.certificate_id.clone(), job);
+    }
+
+    #[test]
+    fn token_id_comes_from_the_contract_badge_log() {
+        let receipt = json!({ "status": "0x1", "logs": [badge_log(&[0xcd; 20], 99), badge_log(&CONTRACT, 7)] });
+        assert_eq!(minted_token_id(&receipt, &CONTRACT).unwrap(), 7);
+    }
+
+    #[test]
+    fn receipt_without_badge_log_is_an_error() {
+        let mut log = badge_log(&CONTRACT, 7);
+        log["topics"][0] = json!(format!("0x{}", "1".repeat(64)));
+        assert!(minted_token_id(&json!({ "logs": [log] }), &CONTRACT).is_err());
+        assert!(minted_token_id(&json!({ "logs": [] }), &CONTRACT).is_err());
+    }
+
+    #[test]
+    fn retry_requeues_a_failed_transfer_without_minting() {
+        let (relayer, receiver) = idle_relayer();
+        insert_job(&relayer, RelayStatus::TransferFailed {
+            token_id: 7,
+            mint_tx: "0xmint".to_string(),
+            error: "reverted".to_string(),
+        });
+
+        assert_eq!(relayer.retry("CERT_1"), Some(Ok(RelayStatus::Queued)));
+        assert_eq!(relayer.job("CERT_1").unwrap().status, RelayStatus::Queued);
+
+        let request = receiver.try_recv().unwrap();
+        assert_eq!(request.wallet, WALLET);
+        match request.step {
+            RelayStep::Transfer { token_id, mint_tx } => {
+                assert_eq!(token_id, 7);
+                assert_eq!(mint_tx, "0xmint");
+            }
+            RelayStep::Mint(_) => panic!("retry must not mint again"),
+        }
+    }
+
+    #[test]
+    fn retry_rejects_jobs_that_are_not_stuck() {
+        let (relayer, receiver) = idle_relayer();
+        assert_eq!(relayer.retry("CERT_1"), None);
+
+        let completed = RelayStatus::Completed {
+            token_id: 7,
+            mint_tx: "0xmint".to_string(),
+            transfer_tx: "0xtransfer".to_string(),
+        };
+        insert_job(&relayer, completed.clone());
+        assert_eq!(relayer.retry("CERT_1"), Some(Err(completed)));
+        assert!(receiver.try_recv().is_err());
+    }
+
+    #[test]
+    fn stopped_worker_fails_the_job() {
+        let (relayer, receiver) = idle_relayer();
+        drop(receiver);
+        insert_job(&relayer, RelayStatus::TransferFailed {
+            token_id: 7,
+            mint_tx: "0xmint".to_string(),
+            error: "reverted".to_string(),
+        });
+
+        let Some(Ok(RelayStatus::Failed { .. })) = relayer.retry("CERT_1") else {
+            panic!("expected the retry to fail");
+        };
+        assert!(matches!(relayer.job("CERT_1").unwrap().status, RelayStatus::Failed { .. }));
+    }
+}
@@ -0,0 +1,192 @@
+use qrcode::{Color, QrCode};
+use crate::certificates::SignedCertificate;
+use crate::credentials;
+use crate::scoring;
+
+// A4 landscape, in points
+const PAGE_WIDTH: f32 = 842.0;
+const PAGE_HEIGHT: f32 = 595.0;
+const QR_SIZE: f32 = 130.0;
+
+/// What the badge shows beyond the signed payload.
+#[derive(Debug, Clone)]
+pub struct BadgeView<'a> {
+    pub certificate: &'a SignedCertificate,
+    pub flagged: bool,
+    pub verification_url: String,
+}
+
+fn escape_xml(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+impl BadgeView<'_> {
+    fn cheating_likelihood(&self) -> u32 {
+        (self.certificate.payload.cheating_likelihood_bps / 100).min(100) as u32
+    }
+
+    fn status(&self) -> (&'static str, &'static str) {
+        if self.flagged { ("Flagged", "#FF6B6B") } else { ("Verified", "#4CAF50") }
+    }
+
+    /// The badge exactly as `EnhancedTalentPassport._generateSVG` draws it.
+    pub fn svg(&self) -> String {
+        let payload = &self.certificate.payload;
+        let (status, status_color) = self.status();
+        let cheating_likelihood = self.cheating_likelihood();
+        let cheating_text = if cheating_likelihood == 0 {
+            "No Cheating Detected".to_string()
+        } else {
+            format!("CLS: {}%", cheating_likelihood)
+        };
+
+        format!(
+            concat!(
+                r#"<svg width="400" height="350" xmlns="http://www.w3.org/2000/svg">"#,
+                r#"<rect width="400" height="350" fill="{background}"/>"#,
+                r#"<rect x="20" y="20" width="360" height="310" fill="white" rx="10"/>"#,
+                r##"<text x="200" y="60" text-anchor="middle" font-family="Arial" font-size="24" fill="#333">Talent Passport</text>"##,
+                r##"<text x="200" y="90" text-anchor="middle" font-family="Arial" font-size="16" fill="#666">{skill}</text>"##,
+                r##"<text x="200" y="130" text-anchor="middle" font-family="Arial" font-size="14" fill="#888">Level: {level}</text>"##,
+                r#"<text x="200" y="155" text-anchor="middle" font-family="Arial" font-size="12" fill="{status_color}">Status: {status}</text>"#,
+                r##"<text x="200" y="180" text-anchor="middle" font-family="Arial" font-size="11" fill="#999">{cheating_text}</text>"##,
+                r##"<text x="200" y="220" text-anchor="middle" font-family="Arial" font-size="10" fill="#aaa">Private Proof of Talent</text>"##,
+                r##"<text x="200" y="240" text-anchor="middle" font-family="Arial" font-size="9" fill="#bbb">FHE + Behavior Analysis</text>"##,
+                r##"<rect x="100" y="260" width="200" height="8" fill="#f0f0f0" rx="4"/>"##,
+                r##"<rect x="100" y="260" width="{bar}" height="8" fill="#FF6B35" rx="4"/>"##,
+                r##"<text x="200" y="285" text-anchor="middle" font-family="Arial" font-size="10" fill="#666">Cheating Likelihood Score</text>"##,
+                "</svg>",
+            ),
+            background = scoring::level_color(payload.level),
+            skill = escape_xml(&payload.quiz_type),
+            level = scoring::level_name(payload.level),
+            status_color = status_color,
+            status = status,
+            cheating_text = cheating_text,
+            bar = cheating_likelihood * 2,
+        )
+    }
+
+    /// A one-page printable certificate with the verification URL as text
+    /// and as a QR code.
+    pub fn pdf(&self) -> Vec<u8> {
+        let payload = &self.certificate.payload;
+        let (status, _) = self.status();
+        let mut page = PdfPage::default();
+
+        let (r, g, b) = hex_color(scoring::level_color(payload.level));
+        page.fill_rect(0.0, 0.0, PAGE_WIDTH, PAGE_HEIGHT, (r, g, b));
+        page.fill_rect(20.0, 20.0, PAGE_WIDTH - 40.0, PAGE_HEIGHT - 40.0, (1.0, 1.0, 1.0));
+
+        let dark = (0.2, 0.2, 0.2);
+        let muted = (0.45, 0.45, 0.45);
+        page.text(60.0, 500.0, 32.0, dark, "Talent Passport Certificate");
+        page.text(60.0, 470.0, 14.0, muted, "Private Proof of Talent - FHE + Behavior Analysis");
+
+        page.text(60.0, 410.0, 16.0, dark, &format!("Awarded to {}", payload.user_id));
+        page.text(60.0, 380.0, 22.0, dark, &format!(
+            "{} - Level {} ({})",
+            payload.quiz_type, payload.level, scoring::level_name(payload.level),
+        ));
+        page.text(60.0, 350.0, 14.0, dark, &format!("Status: {}", status));
+
+        let mut details = vec![
+            format!("Certificate ID: {}", payload.certificate_id),
+            format!("Issued: {}", credentials::format_timestamp(payload.issued_at)),
+        ];
+        if let Some(expires_at) = payload.expires_at {
+            details.push(format!("Valid until: {}", credentials::format_timestamp(expires_at)));
+        }
+        details.push(format!("Signed by issuer key {}", self.certificate.key_id));
+        for (i, line) in details.iter().enumerate() {
+            page.text(60.0, 300.0 - i as f32 * 20.0, 11.0, muted, line);
+        }
+
+        page.text(60.0, 90.0, 11.0, dark, "Verify this certificate at:");
+        page.text(60.0, 72.0, 10.0, muted, &self.verification_url);
+
+        if let Ok(code) = QrCode::new(self.verification_url.as_bytes()) {
+            let width = code.width();
+            let module = QR_SIZE / width as f32;
+            let (left, bottom) = (PAGE_WIDTH - 60.0 - QR_SIZE, 60.0);
+            for (i, color) in code.to_colors().into_iter().enumerate() {
+                if color == Color::Dark {
+                    let (x, y) = (i % width, i / width);
+                    page.fill_rect(left + x as f32 * module, bottom + QR_SIZE - (y + 1) as f32 * module, module, module, (0.0, 0.0, 0.0));
+                }
+            }
+        }
+
+        page.finish()
+    }
+}
+
+fn hex_color(color: &str) -> (f32, f32, f32) {
+    let channel = |i: usize| {
+        u8::from_str_radix(color.get(i..i + 2).unwrap_or("00"), 16).unwrap_or(0) as f32 / 255.0
+    };
+    (channel(1), channel(3), channel(5))
+}
+
+/// Minimal single-page PDF writer: filled rectangles and Helvetica text.
+#[derive(Default)]
+struct PdfPage {
+    content: String,
+}
+
+impl PdfPage {
+    fn fill_rect(&mut self, x: f32, y: f32, width: f32, height: f32, (r, g, b): (f32, f32, f32)) {
+        self.content.push_str(&format!(
+            "{:.3} {:.3} {:.3} rg {:.2} {:.2} {:.2} {:.2} re f\n",
+            r, g, b, x, y, width, height,
+        ));
+    }
+
+    /// Text is limited to printable ASCII, which the standard fonts cover.
+    fn text(&mut self, x: f32, y: f32, size: f32, (r, g, b): (f32, f32, f32), text: &str) {
+        let escaped: String = text.chars()
+            .map(|c| if (' '..='~').contains(&c) { c } else { '?' })
+            .collect::<String>()
+            .replace('\\', "\\\\")
+            .replace('(', "\\(")
+            .replace(')', "\\)");
+        self.content.push_str(&format!(
+            "BT /F1 {:.1} Tf {:.3} {:.3} {:.3} rg {:.2} {:.2} Td ({}) Tj ET\n",
+            size, r, g, b, x, y, escaped,
+        ));
+    }
+
+    fn finish(self) -> Vec<u8> {
+        let objects = [
+            "<< /Type /Catalog /Pages 2 0 R >>".to_string(),
+            "<< /Type /Pages /Kids [3 0 R] /Count 1 >>".to_string(),
+            format!(
+                "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 {} {}] /Resources << /Font << /F1 4 0 R >> >> /Contents 5 0 R >>",
+                PAGE_WIDTH, PAGE_HEIGHT,
+            ),
+            "<< /Type /Font /Subtype /Type1 /BaseFont /Helvetica >>".to_string(),
+            format!("<< /Length {} >>\nstream\n{}endstream", self.content.len(), self.content),
+        ];
+
+        let mut pdf = String::from("%PDF-1.4\n");
+        let mut offsets = Vec::new();
+        for (i, object) in objects.iter().enumerate() {
+            offsets.push(pdf.len());
+            pdf.push_str(&format!("{} 0 obj\n{}\nendobj\n", i + 1, object));
+        }
+        let xref = pdf.len();
+        pdf.push_str(&format!("xref\n0 {}\n0000000000 65535 f \n", objects.len() + 1));
+        for offset in offsets {
+            pdf.push_str(&format!("{:010} 00000 n \n", offset));
+        }
+        pdf.push_str(&format!(
+            "trailer\n<< /Size {} /Root 1 0 R >>\nstartxref\n{}\n%%EOF\n",
+            objects.len() + 1, xref,
+        ));
+        pdf.into_bytes()
+    }
+}
@@ -0,0 +1,83 @@
+use serde_json::{json, Value};
+use std::fmt;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Minimal blocking Ethereum JSON-RPC client.
+pub struct RpcClient {
+    url: String,
+    agent: ureq::Agent,
+    next_id: AtomicU64,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum RpcError {
+    Transport(String),
+    Node { code: i64, message: String },
+    UnexpectedResponse(String),
+}
+
+impl fmt::Display for RpcError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RpcError::Transport(e) => write!(f, "JSON-RPC transport error: {}", e),
+            RpcError::Node { code, message } => write!(f, "node error {}: {}", code, message),
+            RpcError::UnexpectedResponse(e) => write!(f, "unexpected JSON-RPC response: {}", e),
+        }
+    }
+}
+
+/// Parses a 0x-prefixed hex quantity such as a block number.
+pub fn parse_quantity(value: &Value) -> Result<u64, RpcError> {
+    value.as_str()
+        .and_then(|s| s.strip_prefix("0x"))
+        .and_then(|s| u64::from_str_radix(s, 16).ok())
+        .ok_or_else(|| RpcError::UnexpectedResponse(format!("expected a hex quantity, got {}", value)))
+}
+
+pub fn quantity(value: u128) -> String {
+    format!("0x{:x}", value)
+}
+
+impl RpcClient {
+    pub fn new(url: &str) -> Self {
+        RpcClient {
+            url: url.to_string(),
+            agent: ureq::AgentBuilder::new().timeout(REQUEST_TIMEOUT).build(),
+            next_id: AtomicU64::new(1),
+        }
+    }
+
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    pub fn call(&self, method: &str, params: Value) -> Result<Value, RpcError> {
+        let request = json!({
+            "jsonrpc": "2.0",
+            "id": self.next_id.fetch_add(1, Ordering::Relaxed),
+            "method": method,
+            "params": params,
+        });
+        let response: Value = self.agent.post(&self.url)
+            .send_json(request)
+            .map_err(|e| RpcError::Transport(e.to_string()))?
+            .into_json()
+            .map_err(|e| RpcError::UnexpectedResponse(e.to_string()))?;
+
+        if let Some(error) = response.get("error") {
+            return Err(RpcError::Node {
+                code: error["code"].as_i64().unwrap_or_default(),
+                message: error["message"].as_str().unwrap_or_default().to_string(),
+            });
+        }
+        response.get("result").cloned()
+            .ok_or_else(|| RpcError::UnexpectedResponse("missing result".to_string()))
+    }
+
+    pub fn block_number(&self) -> Result<u64, RpcError> {
+        parse_quantity(&self.call("eth_blockNumber", json!([]))?)
+    }
+}
@@ -0,0 +1,407 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+use std::path::Path;
+use crate::accommodations::Accommodation;
+use crate::cheating_model::CheatingModel;
+use crate::irt::AbilityEstimate;
+
+// Levels the passport contract knows how to render
+pub const MIN_LEVEL: u8 = 1;
+pub const MAX_LEVEL: u8 = 5;
+
+/// Level names as the passport contract renders them.
+pub fn level_name(level: u8) -> &'static str {
+    match level {
+        5 => "Expert",
+        4 => "Advanced",
+        3 => "Intermediate",
+        2 => "Beginner",
+        _ => "Novice",
+    }
+}
+
+/// Badge background colors as the passport contract renders them.
+pub fn level_color(level: u8) -> &'static str {
+    match level {
+        5 => "#4CAF50",
+        4 => "#8BC34A",
+        3 => "#FFC107",
+        2 => "#FF9800",
+        _ => "#F44336",
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct LevelBand {
+    pub min_score: f32,
+    pub level: u8,
+}
+
+/// Level threshold on the IRT ability (logit) scale.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct AbilityBand {
+    pub min_ability: f64,
+    pub level: u8,
+}
+
+/// What happens to an attempt whose cheating likelihood crosses the threshold.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(tag = "action", rename_all = "snake_case")]
+pub enum FlagHandling {
+    DropLevel { level: u8 }, // fail and replace the level
+    FailOnly,                // fail but keep the earned level
+    RecordOnly,              // report the flag without changing the outcome
+}
+
+/// How items are weighted when computing the score used for pass and level.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Default, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ScoringMode {
+    #[default]
+    Uniform,
+    DifficultyWeighted,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+pub struct MinTimeRules {
+    pub min_total_seconds: Option<u32>,
+    pub min_seconds_per_question: Option<u32>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct ScoringPolicy {
+    pub pass_threshold: f32,
+    pub level_bands: Vec<LevelBand>, // checked highest min_score first
+    #[serde(default)]
+    pub ability_bands: Vec<AbilityBand>, // when set, levels come from ability instead of score
+    #[serde(default)]
+    pub flag_threshold: Option<f32>, // overrides the cheating model's threshold
+    pub flag_handling: FlagHandling,
+    #[serde(default)]
+    pub min_time: MinTimeRules,
+    #[serde(default)]
+    pub mode: ScoringMode,
+    #[serde(default)]
+    pub partial_credit: bool,       // multi-part items earn a share of their weight
+    #[serde(default)]
+    pub negative_marking: f32,      // share of an item's weight lost for a wrong answer
+    #[serde(default)]
+    pub validity_days: Option<u32>, // certificates expire and need recertification after this
+}
+
+/// One evaluated item. `credit` is the fraction of parts answered correctly.
+#[derive(Debug, Clone, Copy)]
+pub struct ItemResult {
+    pub credit: f32,
+    pub answered: bool,
+    pub difficulty: f32,
+}
+
+#[derive(Debug, Serialize, Clone, Copy)]
+pub struct ScoreBreakdown {
+    pub raw_score: f32,      // fully correct items / total items
+    pub weighted_score: f32, // after weighting, partial credit and negative marking
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct ScoringOutcome {
+    pub passed: bool,
+    pub level: u8,
+    pub min_time_violation: bool,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum PolicyError {
+    ThresholdOutOfRange(f32),
+    AbilityOutOfRange(f64),
+    NegativeMarkingOutOfRange(f32),
+    LevelOutOfRange(u8),
+    NoLevelBands,
+    ZeroValidity,
+}
+
+impl fmt::Display for PolicyError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PolicyError::ThresholdOutOfRange(t) => write!(f, "threshold {} is outside 0..=1", t),
+            PolicyError::AbilityOutOfRange(a) => write!(f, "ability {} is not a finite value", a),
+            PolicyError::NegativeMarkingOutOfRange(p) => {
+                write!(f, "negative marking {} is outside 0..=1", p)
+            }
+            PolicyError::LevelOutOfRange(l) => {
+                write!(f, "level {} is outside {}..={}", l, MIN_LEVEL, MAX_LEVEL)
+            }
+            PolicyError::NoLevelBands => write!(f, "policy has no level bands"),
+            PolicyError::ZeroValidity => write!(f, "validity period must be at least one day"),
+        }
+    }
+}
+
+impl ScoringPolicy {
+    /// The original score bands, with a quiz-specific pass threshold and
+    /// ability bands placed so a three-item session maps like the score bands.
+    pub fn standard(pass_threshold: f32) -> Self {
+        ScoringPolicy {
+            pass_threshold,
+            level_bands: vec![
+                LevelBand { min_score: 0.9, level: 5 },
+                LevelBand { min_score: 0.7, level: 4 },
+                LevelBand { min_score: 0.6, level: 3 },
+                LevelBand { min_score: 0.5, level: 2 },
+            ],
+            ability_bands: vec![
+                AbilityBand { min_ability: 0.8, level: 5 },
+                AbilityBand { min_ability: 0.5, level: 4 },
+                AbilityBand { min_ability: 0.0, level: 3 },
+                AbilityBand { min_ability: -0.5, level: 2 },
+            ],
+            flag_threshold: None,
+            flag_handling: FlagHandling::DropLevel { level: 1 }, // Reduced level if flagged
+            min_time: MinTimeRules::default(),
+            mode: ScoringMode::Uniform,
+            partial_credit: false,
+            negative_marking: 0.0,
+            validity_days: None,
+        }
+    }
+
+    pub fn validate(&self) -> Result<(), PolicyError> {
+        let thresholds = self.level_bands.iter().map(|b| b.min_score)
+            .chain([self.pass_threshold])
+            .chain(self.flag_threshold);
+        for threshold in thresholds {
+            if !(0.0..=1.0).contains(&threshold) {
+                return Err(PolicyError::ThresholdOutOfRange(threshold));
+            }
+        }
+
+        if let Some(band) = self.ability_bands.iter().find(|b| !b.min_ability.is_finite()) {
+            return Err(PolicyError::AbilityOutOfRange(band.min_ability));
+        }
+
+        if !(0.0..=1.0).contains(&self.negative_marking) {
+            return Err(PolicyError::NegativeMarkingOutOfRange(self.negative_marking));
+        }
+
+        if self.validity_days == Some(0) {
+            return Err(PolicyError::ZeroValidity);
+        }
+
+        if self.level_bands.is_empty() {
+            return Err(PolicyError::NoLevelBands);
+        }
+        let levels = self.level_bands.iter().map(|b| b.level)
+            .chain(self.ability_bands.iter().map(|b| b.level));
+        let flag_level = match self.flag_handling {
+            FlagHandling::DropLevel { level } => Some(level),
+            _ => None,
+        };
+        for level in levels.chain(flag_level) {
+            if !(MIN_LEVEL..=MAX_LEVEL).contains(&level) {
+                return Err(PolicyError::LevelOutOfRange(level));
+            }
+        }
+        Ok(())
+    }
+
+    fn item_weight(&self, item: &ItemResult) -> f32 {
+        match self.mode {
+            ScoringMode::Uniform => 1.0,
+            // Small floor so trivial items still count for something
+            ScoringMode::DifficultyWeighted => item.difficulty.max(0.1),
+        }
+    }
+
+    pub fn score(&self, items: &[ItemResult]) -> ScoreBreakdown {
+        if items.is_empty() {
+            return ScoreBreakdown { raw_score: 0.0, weighted_score: 0.0 };
+        }
+
+        let fully_correct = items.iter().filter(|item| item.credit >= 1.0).count();
+        let raw_score = fully_correct as f32 / items.len() as f32;
+
+        let mut earned = 0.0;
+        let mut possible = 0.0;
+        for item in items {
+            let weight = self.item_weight(item);
+            possible += weight;
+
+            let credit = if self.partial_credit || item.credit >= 1.0 { item.credit.clamp(0.0, 1.0) } else { 0.0 };
+            earned += weight * credit;
+            if item.answered && credit == 0.0 {
+                earned -= weight * self.negative_marking;
+            }
+        }
+
+        ScoreBreakdown {
+            raw_score,
+            weighted_score: (earned / possible).max(0.0),
+        }
+    }
+
+    /// When a certificate issued at `issued_at` stops being valid, if ever.
+    pub fn expires_at(&self, issued_at: u64) -> Option<u64> {
+        self.validity_days.map(|days| issued_at + days as u64 * 86_400)
+    }
+
+    pub fn flag_threshold(&self, model: &CheatingModel) -> f32 {
+        self.flag_threshold.unwrap_or(model.flag_threshold)
+    }
+
+    pub fn is_flagged(&self, cheating_likelihood: f32, model: &CheatingModel) -> bool {
+        cheating_likelihood > self.flag_threshold(model)
+    }
+
+    pub fn level_for(&self, score: f32) -> u8 {
+        let mut bands: Vec<&LevelBand> = self.level_bands.iter().collect();
+        bands.sort_by(|a, b| b.min_score.total_cmp(&a.min_score));
+        bands.iter()
+            .find(|band| score >= band.min_score)
+            .map(|band| band.level)
+            .unwrap_or(MIN_LEVEL)
+    }
+
+    pub fn level_for_ability(&self, ability: f64) -> u8 {
+        let mut bands: Vec<&AbilityBand> = self.ability_bands.iter().collect();
+        bands.sort_by(|a, b| b.min_ability.total_cmp(&a.min_ability));
+        bands.iter()
+            .find(|band| ability >= band.min_ability)
+            .map(|band| band.level)
+            .unwrap_or(MIN_LEVEL)
+    }
+
+    /// Checks observed answer times against the minimum time rules, with
+    /// the limits relaxed for time accommodations.
+    pub fn violates_min_time(&self, answer_times: &[u32], accommodation: &Accommodation) -> bool {
+        let total: u64 = answer_times.iter().map(|&t| u64::from(t)).sum();
+        let below_total = self.min_time.min_total_seconds
+            .is_some_and(|min| (total as f32) < accommodation.time_floor(min as f32));
+        let below_per_question = self.min_time.min_seconds_per_question
+            .is_some_and(|min| answer_times.iter().any(|&t| (t as f32) < accommodation.time_floor(min as f32)));
+        below_total || below_per_question
+    }
+
+    pub fn outcome(
+        &self,
+        score: f32,
+        ability: &AbilityEstimate,
+        is_flagged: bool,
+        answer_times: &[u32],
+        accommodation: &Accommodation,
+    ) -> ScoringOutcome {
+        let min_time_violation = self.violates_min_time(answer_times, accommodation);
+        let mut passed = score >= self.pass_threshold && !min_time_violation;
+        let mut level = if self.ability_bands.is_empty() {
+            self.level_for(score)
+        } else {
+            self.level_for_ability(ability.theta)
+        };
+
+        if is_flagged {
+            match self.flag_handling {
+                FlagHandling::DropLevel { level: flagged_level } => {
+                    passed = false;
+                    level = flagged_level;
+                }
+                FlagHandling::FailOnly => passed = false,
+                FlagHandling::RecordOnly => {}
+            }
+        }
+
+        ScoringOutcome {
+            passed,
+            level,
+            min_time_violation,
+        }
+    }
+}
+
+/// Loads per-quiz policy overrides from a JSON object keyed by quiz type.
+pub fn load_policies(path: &Path) -> std::io::Result<HashMap<String, ScoringPolicy>> {
+    let contents = std::fs::read_to_string(path)?;
+    serde_json::from_str(&contents).map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::behavior::{BehaviorData, MAX_ANSWER_SECONDS};
+
+    fn item(credit: f32, difficulty: f32) -> ItemResult {
+        ItemResult { credit, answered: true, difficulty }
+    }
+
+    fn timed_policy() -> ScoringPolicy {
+        let mut policy = ScoringPolicy::standard(0.6);
+        policy.min_time = MinTimeRules { min_total_seconds: Some(60), min_seconds_per_question: Some(5) };
+        policy
+    }
+
+    #[test]
+    fn partial_credit_and_negative_marking() {
+        let items = [item(1.0, 0.5), item(0.5, 0.5), item(0.0, 0.5), item(0.0, 0.5)];
+        let mut policy = ScoringPolicy::standard(0.6);
+        assert_eq!(policy.score(&items).weighted_score, 0.25);
+
+        policy.partial_credit = true;
+        assert_eq!(policy.score(&items).weighted_score, 0.375);
+
+        policy.negative_marking = 0.25;
+        let score = policy.score(&items);
+        assert_eq!((score.raw_score, score.weighted_score), (0.25, 0.25));
+
+        policy.negative_marking = 1.0;
+        assert_eq!(policy.score(&[item(0.0, 0.5)]).weighted_score, 0.0, "never below zero");
+    }
+
+    #[test]
+    fn difficulty_weighting_favours_hard_items() {
+        let mut policy = ScoringPolicy::standard(0.6);
+        policy.mode = ScoringMode::DifficultyWeighted;
+        let hard_right = policy.score(&[item(1.0, 0.9), item(0.0, 0.1)]).weighted_score;
+        let easy_right = policy.score(&[item(0.0, 0.9), item(1.0, 0.1)]).weighted_score;
+        assert!(hard_right > 0.8 && easy_right < 0.2, "{} {}", hard_right, easy_right);
+    }
+
+    #[test]
+    fn min_time_totals_cannot_wrap() {
+        let policy = timed_policy();
+        let standard = Accommodation::default();
+        assert!(policy.violates_min_time(&[10, 10, 10], &standard));
+        assert!(policy.violates_min_time(&[40, 40, 2], &standard));
+        assert!(!policy.violates_min_time(&[20, 20, 20], &standard));
+
+        // u32::MAX + 61 would wrap to a total under the minimum
+        assert!(!policy.violates_min_time(&[u32::MAX, 61], &standard));
+        let behavior = BehaviorData {
+            answer_times: vec![u32::MAX, 7],
+            switch_counts: vec![u32::MAX],
+            start_time: 0,
+            end_time: 0,
+            events: Vec::new(),
+            input: Default::default(),
+        }.sanitized();
+        assert_eq!(behavior.answer_times, [MAX_ANSWER_SECONDS, 7]);
+        assert!(!policy.violates_min_time(&behavior.answer_times, &standard));
+    }
+
+    #[test]
+    fn flags_and_min_time_shape_the_outcome() {
+        let policy = timed_policy();
+        let ability = AbilityEstimate { theta: 0.6, standard_error: 0.4 };
+        let standard = Accommodation::default();
+
+        let clean = policy.outcome(0.8, &ability, false, &[30, 30], &standard);
+        assert_eq!((clean.passed, clean.level, clean.min_time_violation), (true, 4, false));
+
+        let flagged = policy.outcome(0.8, &ability, true, &[30, 30], &standard);
+        assert_eq!((flagged.passed, flagged.level), (false, 1));
+
+        let rushed = policy.outcome(0.8, &ability, false, &[3, 30], &standard);
+        assert_eq!((rushed.passed, rushed.level, rushed.min_time_violation), (false, 4, true));
+
+        let extended = Accommodation { time_multiplier: 2.0, ..Default::default() };
+        assert!(policy.outcome(0.8, &ability, false, &[3, 30], &extended).passed);
+    }
+}
@@ -0,0 +1,125 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::Mutex;
+use crate::cheating_model::{CheatingModel, DetectorSignals};
+
+/// A detector model scored on every submission without affecting results.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ShadowCandidate {
+    pub name: String,
+    pub model: CheatingModel,
+}
+
+#[derive(Debug, Serialize, Clone, Default)]
+pub struct ShadowStats {
+    pub evaluations: u64,
+    pub disagreements: u64,
+    pub shadow_only_flags: u64, // shadow would flag, live did not
+    pub live_only_flags: u64,   // live flagged, shadow would not
+    pub mean_score_delta: f32,  // mean of shadow - live likelihood
+}
+
+#[derive(Debug, Serialize)]
+pub struct ShadowReport {
+    pub candidate: String,
+    pub flag_threshold: f32,
+    pub disagreement_rate: f32,
+    pub stats: ShadowStats,
+}
+
+#[derive(Debug, Default)]
+pub struct ShadowEvaluator {
+    candidates: Vec<ShadowCandidate>,
+    stats: Mutex<HashMap<String, ShadowStats>>,
+}
+
+impl ShadowEvaluator {
+    pub fn new(candidates: Vec<ShadowCandidate>) -> Self {
+        ShadowEvaluator {
+            candidates,
+            stats: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Loads candidates from a JSON array of `{"name", "model"}` objects,
+    /// validated like the live model.
+    pub fn load(path: &Path) -> std::io::Result<Self> {
+        let contents = std::fs::read_to_string(path)?;
+        let candidates: Vec<ShadowCandidate> = serde_json::from_str(&contents)
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?;
+        for candidate in &candidates {
+            candidate.model.validate().map_err(|e| std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                format!("shadow model {}: {}", candidate.name, e),
+            ))?;
+        }
+        Ok(Self::new(candidates))
+    }
+
+    pub fn len(&self) -> usize {
+        self.candidates.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.candidates.is_empty()
+    }
+
+    /// Scores a submission with every candidate and logs it next to the live decision.
+    pub fn observe(&self, session_id: &str, signals: &DetectorSignals, live_likelihood: f32, live_flagged: bool) {
+        if self.candidates.is_empty() {
+            return;
+        }
+
+        let mut stats = self.stats.lock().unwrap();
+        for candidate in &self.candidates {
+            let shadow_likelihood = candidate.model.likelihood(signals);
+            let shadow_flagged = candidate.model.is_flagged(shadow_likelihood);
+            println!(
+                "🌓 Shadow [{}] session {}: live {:.2} ({}) | shadow {:.2} ({})",
+                candidate.name,
+                session_id,
+                live_likelihood,
+                if live_flagged { "flagged" } else { "clean" },
+                shadow_likelihood,
+                if shadow_flagged { "flagged" } else { "clean" },
+            );
+
+            let entry = stats.entry(candidate.name.clone()).or_default();
+            entry.evaluations += 1;
+            entry.mean_score_delta += (shadow_likelihood - live_likelihood - entry.mean_score_delta)
+                / entry.evaluations as f32;
+            match (live_flagged, shadow_flagged) {
+                (false, true) => {
+                    entry.disagreements += 1;
+                    entry.shadow_only_flags += 1;
+                }
+                (true, false) => {
+                    entry.disagreements += 1;
+                    entry.live_only_flags += 1;
+                }
+                _ => {}
+            }
+        }
+    }
+
+    pub fn report(&self) -> Vec<ShadowReport> {
+        let stats = self.stats.lock().unwrap();
+        self.candidates.iter()
+            .map(|candidate| {
+                let stats = stats.get(&candidate.name).cloned().unwrap_or_default();
+                let disagreement_rate = if stats.evaluations > 0 {
+                    stats.disagreements as f32 / stats.evaluations as f32
+                } else {
+                    0.0
+                };
+                ShadowReport {
+                    candidate: candidate.name.clone(),
+                    flag_threshold: candidate.model.flag_threshold,
+                    disagreement_rate,
+                    stats,
+                }
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,230 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+// Upper bound on events accepted per submission; anything beyond is ignored
+pub const MAX_EVENTS: usize = 2000;
+// Allowed drift between client event clocks and the reported session window
+const CLOCK_SKEW_MS: u64 = 5_000;
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ClientEvent {
+    VisibilityChange { timestamp: u64, hidden: bool },
+    WindowBlur { timestamp: u64 },
+    WindowFocus { timestamp: u64 },
+    Copy { timestamp: u64, question_index: Option<usize> },
+    Paste { timestamp: u64, question_index: Option<usize> },
+    AnswerSelected { timestamp: u64, question_index: usize, option: u8 },
+    #[serde(other)]
+    Unknown,
+}
+
+#[derive(Debug, Default)]
+pub struct EventStream {
+    pub events: Vec<ClientEvent>,
+    pub dropped: usize,   // malformed, unknown or out-of-window events
+    pub truncated: bool,  // stream exceeded MAX_EVENTS
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct TelemetrySummary {
+    pub hidden_count: u32,
+    pub hidden_ratio: f32,          // share of the session spent with the tab hidden
+    pub blur_count: u32,
+    pub copy_count: u32,
+    pub paste_count: u32,
+    pub answer_selections: u32,
+    pub rapid_selection_ratio: f32, // share of question-to-question selections under 1s apart
+    pub dropped_events: u32,
+}
+
+impl ClientEvent {
+    pub fn timestamp(&self) -> Option<u64> {
+        match self {
+            ClientEvent::VisibilityChange { timestamp, .. }
+            | ClientEvent::WindowBlur { timestamp }
+            | ClientEvent::WindowFocus { timestamp }
+            | ClientEvent::Copy { timestamp, .. }
+            | ClientEvent::Paste { timestamp, .. }
+            | ClientEvent::AnswerSelected { timestamp, .. } => Some(*timestamp),
+            ClientEvent::Unknown => None,
+        }
+    }
+}
+
+/// Parses raw client events, dropping anything malformed or outside the
+/// session window. Timestamps are milliseconds; the window is in seconds.
+pub fn parse_events(raw: &[Value], start_time: u64, end_time: u64) -> EventStream {
+    let truncated = raw.len() > MAX_EVENTS;
+    let window_start = start_time.saturating_mul(1000).saturating_sub(CLOCK_SKEW_MS);
+    let window_end = end_time.saturating_mul(1000).saturating_add(CLOCK_SKEW_MS);
+    let check_window = end_time >= start_time && start_time > 0;
+
+    let mut stream = EventStream {
+        truncated,
+        dropped: raw.len().saturating_sub(MAX_EVENTS),
+        ..Default::default()
+    };
+
+    for value in raw.iter().take(MAX_EVENTS) {
+        let event = match ClientEvent::deserialize(value) {
+            Ok(event) => event,
+            Err(_) => {
+                stream.dropped += 1;
+                continue;
+            }
+        };
+
+        match event.timestamp() {
+            Some(ts) if !check_window || (window_start..=window_end).contains(&ts) => {
+                stream.events.push(event);
+            }
+            _ => stream.dropped += 1,
+        }
+    }
+
+    stream.events.sort_by_key(|e| e.timestamp().unwrap_or(0));
+    stream
+}
+
+/// Last option picked for each question, in question order.
+pub fn selections(stream: &EventStream, question_count: usize) -> Vec<Option<u8>> {
+    let mut selections = vec![None; question_count];
+    for event in &stream.events {
+        if let ClientEvent::AnswerSelected { question_index, option, .. } = *event {
+            if let Some(slot) = selections.get_mut(question_index) {
+                *slot = Some(option);
+            }
+        }
+    }
+    selections
+}
+
+/// Like `selections` with `submitted` answers filled in over the reported ones.
+pub fn final_selections(stream: &EventStream, question_count: usize, submitted: &[Option<u8>]) -> Vec<Option<u8>> {
+    selections(stream, question_count).into_iter().enumerate()
+        .map(|(i, reported)| submitted.get(i).copied().flatten().or(reported))
+        .collect()
+}
+
+pub fn summarize(stream: &EventStream, start_time: u64, end_time: u64) -> TelemetrySummary {
+    let mut summary = TelemetrySummary {
+        dropped_events: stream.dropped as u32,
+        ..Default::default()
+    };
+
+    let mut hidden_since: Option<u64> = None;
+    let mut hidden_ms: u64 = 0;
+    let mut last_selection: Option<(usize, u64)> = None;
+    let mut question_changes = 0u32;
+    let mut rapid_changes = 0u32;
+
+    for event in &stream.events {
+        match *event {
+            ClientEvent::VisibilityChange { timestamp, hidden: true } => {
+                summary.hidden_count += 1;
+                hidden_since.get_or_insert(timestamp);
+            }
+            ClientEvent::VisibilityChange { timestamp, hidden: false } => {
+                if let Some(since) = hidden_since.take() {
+                    hidden_ms += timestamp.saturating_sub(since);
+                }
+            }
+            ClientEvent::WindowBlur { .. } => summary.blur_count += 1,
+            ClientEvent::WindowFocus { .. } => {}
+            ClientEvent::Copy { .. } => summary.copy_count += 1,
+            ClientEvent::Paste { .. } => summary.paste_count += 1,
+            ClientEvent::AnswerSelected { timestamp, question_index, .. } => {
+                summary.answer_selections += 1;
+                if let Some((prev_index, prev_ts)) = last_selection {
+                    if prev_index != question_index {
+                        question_changes += 1;
+                        if timestamp.saturating_sub(prev_ts) < 1000 {
+                            rapid_changes += 1;
+                        }
+                    }
+                }
+                last_selection = Some((question_index, timestamp));
+            }
+            ClientEvent::Unknown => {}
+        }
+    }
+
+    // Tab still hidden at submission time
+    if let Some(since) = hidden_since {
+        hidden_ms += end_time.saturating_mul(1000).saturating_sub(since);
+    }
+
+    let session_ms = end_time.saturating_sub(start_time).saturating_mul(1000);
+    if session_ms > 0 {
+        summary.hidden_ratio = (hidden_ms as f32 / session_ms as f32).min(1.0);
+    }
+    if question_changes > 0 {
+        summary.rapid_selection_ratio = rapid_changes as f32 / question_changes as f32;
+    }
+
+    summary
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn events_outside_the_window_or_malformed_are_dropped() {
+        let raw = vec![
+            json!({"type": "paste", "timestamp": 1_010_000, "question_index": 0}),
+            json!({"type": "paste", "timestamp": 2_000_000, "question_index": 0}),
+            json!({"type": "paste"}),
+            json!({"type": "teleport", "timestamp": 1_010_000}),
+            json!({"type": "copy", "timestamp": 1_005_000, "question_index": null}),
+        ];
+        let stream = parse_events(&raw, 1000, 1400);
+        assert_eq!(stream.dropped, 3);
+        assert!(!stream.truncated);
+        // Sorted by timestamp
+        assert!(matches!(stream.events[0], ClientEvent::Copy { .. }));
+        assert!(matches!(stream.events[1], ClientEvent::Paste { .. }));
+    }
+
+    #[test]
+    fn oversized_streams_are_truncated() {
+        let raw = vec![json!({"type": "window_blur", "timestamp": 1_001_000}); MAX_EVENTS + 5];
+        let stream = parse_events(&raw, 1000, 1400);
+        assert!(stream.truncated);
+        assert_eq!((stream.events.len(), stream.dropped), (MAX_EVENTS, 5));
+    }
+
+    #[test]
+    fn summary_measures_hidden_time_and_rapid_selection() {
+        let raw = vec![
+            json!({"type": "visibility_change", "timestamp": 1_000_000, "hidden": true}),
+            json!({"type": "visibility_change", "timestamp": 1_100_000, "hidden": false}),
+            json!({"type": "answer_selected", "timestamp": 1_200_000, "question_index": 0, "option": 1}),
+            json!({"type": "answer_selected", "timestamp": 1_200_500, "question_index": 1, "option": 2}),
+            json!({"type": "answer_selected", "timestamp": 1_300_000, "question_index": 2, "option": 0}),
+            // Still hidden at submission: counts until end_time
+            json!({"type": "visibility_change", "timestamp": 1_300_000, "hidden": true}),
+        ];
+        let stream = parse_events(&raw, 1000, 1400);
+        let summary = summarize(&stream, 1000, 1400);
+        assert_eq!(summary.hidden_count, 2);
+        assert!((summary.hidden_ratio - 0.5).abs() < 1e-6);
+        assert_eq!(summary.answer_selections, 3);
+        assert!((summary.rapid_selection_ratio - 0.5).abs() < 1e-6);
+    }
+
+    #[test]
+    fn submitted_answers_override_reported_selections() {
+        let raw = vec![
+            json!({"type": "answer_selected", "timestamp": 1_010_000, "question_index": 0, "option": 1}),
+            json!({"type": "answer_selected", "timestamp": 1_020_000, "question_index": 1, "option": 2}),
+            json!({"type": "answer_selected", "timestamp": 1_030_000, "question_index": 7, "option": 2}),
+        ];
+        let stream = parse_events(&raw, 1000, 1400);
+        assert_eq!(selections(&stream, 3), [Some(1), Some(2), None]);
+        assert_eq!(final_selections(&stream, 3, &[Some(3), None, Some(0)]), [Some(3), Some(2), Some(0)]);
+        assert_eq!(final_selections(&stream, 3, &[]), [Some(1), Some(2), None]);
+    }
+}